@@ -11,15 +11,15 @@ async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt().with_max_level(Level::INFO).init();
     dotenvy::dotenv().ok();
 
-    let config = AppConfig::from_env()?;
-
-    let database = Database::connect(&config.database_url, AlpacaMessageHandler).await?;
+    let config = AppConfig::load()?;
+    let alpaca = config.alpaca()?;
+    let database = Database::connect(config.database_url()?, AlpacaMessageHandler).await?;
     database.initialize_schema().await?;
 
     let websocket = AlpacaWebSocketClient::new(
-        &config.alpaca_ws_url,
-        &config.alpaca_api_key,
-        &config.alpaca_api_secret,
+        &alpaca.ws_url,
+        alpaca.api_key.expose(),
+        alpaca.api_secret.expose(),
         &[],
         &["ETH/USD"],
         &[],
@@ -0,0 +1,61 @@
+//! Example: Poll Polymarket midpoints, spreads and price history into PostgreSQL.
+//!
+//! Tracks every outcome token of active markets tagged `Politics` whose order book
+//! holds at least $1,000 of resting liquidity.
+//!
+//! Required environment variables:
+//! - `DATABASE_URL`: PostgreSQL connection string
+//!
+//! Run with:
+//! ```bash
+//! cargo run --example polymarket_prices_to_postgres --features polymarket,postgres
+//! ```
+
+#[cfg(all(feature = "polymarket", feature = "postgres"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use tickflow::{
+        config::AppConfig,
+        connectors::polymarket::{PolymarketPricePoller, Watchlist},
+        pipeline::TickflowBuilder,
+        storage::{Database, postgres_handler::polymarket::PolymarketMessageHandler},
+    };
+    use tokio::time::Duration;
+    use tracing::Level;
+
+    // Initialize logging
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    // Load environment variables
+    dotenvy::dotenv().ok();
+    let config = AppConfig::load()?;
+
+    // Setup database connection and schema
+    let database = Database::connect(config.database_url()?, PolymarketMessageHandler).await?;
+    database.initialize_schema().await?;
+
+    // Poll once a minute, backfilling a day of hourly history on the first round
+    let watchlist = Watchlist {
+        tags: vec!["Politics".to_string()],
+        active_only: true,
+        min_liquidity: Some(1_000.0),
+        ..Watchlist::default()
+    };
+    let source =
+        PolymarketPricePoller::new(watchlist, Duration::from_secs(60), 100).with_history("1d", 60);
+
+    // Start the data pipeline
+    let handles = TickflowBuilder::new(source, database)
+        .channel_capacity(config.channel_capacity)
+        .start()
+        .await?;
+
+    // Wait for both tasks to complete
+    tokio::try_join!(handles.source, handles.processor)?;
+    Ok(())
+}
+
+#[cfg(not(all(feature = "polymarket", feature = "postgres")))]
+fn main() {
+    panic!("Enable the `polymarket` and `postgres` features to run this example.");
+}
@@ -0,0 +1,61 @@
+//! Example: Stream Polymarket order book and trade events into PostgreSQL.
+//!
+//! Token IDs are loaded from the `polymarket_tokens` of markets previously stored by the
+//! `polymarket_to_postgres` example, limited to active, open markets.
+//!
+//! Required environment variables:
+//! - `DATABASE_URL`: PostgreSQL connection string
+//!
+//! Run with:
+//! ```bash
+//! cargo run --example polymarket_stream_to_postgres --features polymarket,postgres
+//! ```
+
+#[cfg(all(feature = "polymarket", feature = "postgres"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use tickflow::{
+        config::AppConfig,
+        connectors::polymarket::PolymarketMarketStream,
+        pipeline::TickflowBuilder,
+        storage::{
+            Database,
+            postgres_handler::polymarket::{PolymarketMessageHandler, load_token_ids},
+        },
+    };
+    use tracing::Level;
+
+    // Initialize logging
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    // Load environment variables
+    dotenvy::dotenv().ok();
+    let config = AppConfig::load()?;
+
+    // Setup database connection and schema
+    let database = Database::connect(config.database_url()?, PolymarketMessageHandler).await?;
+    database.initialize_schema().await?;
+
+    // Subscribe to every outcome token of active markets
+    let token_ids = load_token_ids(&database.client(), true).await?;
+    anyhow::ensure!(
+        !token_ids.is_empty(),
+        "no stored markets; run the polymarket_to_postgres example first"
+    );
+    let source = PolymarketMarketStream::new(token_ids);
+
+    // Start the data pipeline
+    let handles = TickflowBuilder::new(source, database)
+        .channel_capacity(config.channel_capacity)
+        .start()
+        .await?;
+
+    // Wait for both tasks to complete
+    tokio::try_join!(handles.source, handles.processor)?;
+    Ok(())
+}
+
+#[cfg(not(all(feature = "polymarket", feature = "postgres")))]
+fn main() {
+    panic!("Enable the `polymarket` and `postgres` features to run this example.");
+}
@@ -0,0 +1,64 @@
+//! Example: Keep Polymarket markets in PostgreSQL up to date and record their changes.
+//!
+//! Every 15 minutes all markets are re-fetched and diffed against the stored ones;
+//! opened, closed, resolved or updated markets are written to `polymarket_market_events`
+//! and `polymarket_market_history`.
+//!
+//! Required environment variables:
+//! - `DATABASE_URL`: PostgreSQL connection string
+//! - `PK` (optional): Polymarket private key; market listings are public without it
+//!
+//! Run with:
+//! ```bash
+//! cargo run --example polymarket_sync_to_postgres --features polymarket,postgres
+//! ```
+
+#[cfg(all(feature = "polymarket", feature = "postgres"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use tickflow::{
+        config::AppConfig,
+        connectors::polymarket::{PolymarketClient, PolymarketMarketSync},
+        pipeline::TickflowBuilder,
+        storage::{
+            Database,
+            postgres_handler::polymarket::{PolymarketMessageHandler, market_loader},
+        },
+    };
+    use tokio::time::Duration;
+    use tracing::Level;
+
+    // Initialize logging
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    // Load environment variables
+    dotenvy::dotenv().ok();
+    let config = AppConfig::load()?;
+
+    // Setup database connection and schema
+    let database = Database::connect(config.database_url()?, PolymarketMessageHandler).await?;
+    database.initialize_schema().await?;
+
+    let client = match config.polymarket.private_key {
+        Some(private_key) => PolymarketClient::new(private_key, 100),
+        None => PolymarketClient::public(100),
+    };
+    // Diff against the stored markets, so changes whose write failed are detected again
+    let source = PolymarketMarketSync::new(client, Duration::from_secs(15 * 60))
+        .reload_baseline(market_loader(database.client()));
+
+    // Start the data pipeline
+    let handles = TickflowBuilder::new(source, database)
+        .channel_capacity(config.channel_capacity)
+        .start()
+        .await?;
+
+    // Wait for both tasks to complete
+    tokio::try_join!(handles.source, handles.processor)?;
+    Ok(())
+}
+
+#[cfg(not(all(feature = "polymarket", feature = "postgres")))]
+fn main() {
+    panic!("Enable the `polymarket` and `postgres` features to run this example.");
+}
@@ -5,7 +5,10 @@
 //!
 //! Required environment variables:
 //! - `DATABASE_URL`: PostgreSQL connection string
-//! - `PK`: Polymarket private key for API authentication
+//! - `PK` (optional): Polymarket private key; market listings are public without it
+//!
+//! Progress is checkpointed in `tickflow_checkpoints`, so an interrupted run resumes
+//! from the last page. Pass `--restart` to discard the checkpoint and start over.
 //!
 //! Run with:
 //! ```bash
@@ -15,11 +18,16 @@
 #[cfg(all(feature = "polymarket", feature = "postgres"))]
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    use std::sync::Arc;
     use tickflow::{
         config::AppConfig,
         connectors::polymarket::PolymarketClient,
+        core::Checkpoint,
         pipeline::TickflowBuilder,
-        storage::{Database, postgres_handler::polymarket::PolymarketMessageHandler},
+        storage::{
+            Database, PostgresCheckpointStore,
+            postgres_handler::polymarket::PolymarketMessageHandler,
+        },
     };
     use tracing::Level;
 
@@ -28,15 +36,27 @@ async fn main() -> anyhow::Result<()> {
 
     // Load environment variables
     dotenvy::dotenv().ok();
-    let config = AppConfig::from_env()?;
+    let config = AppConfig::load()?;
 
     // Setup database connection and schema
-    let database = Database::connect(&config.database_url, PolymarketMessageHandler).await?;
+    let database = Database::connect(config.database_url()?, PolymarketMessageHandler).await?;
     database.initialize_schema().await?;
 
+    // Resume from the last fetched page unless asked to restart
+    let store = PostgresCheckpointStore::new(database.client());
+    store.initialize_schema().await?;
+    let checkpoint = Checkpoint::new(Arc::new(store), "polymarket_markets");
+    if std::env::args().any(|arg| arg == "--restart") {
+        checkpoint.clear().await?;
+    }
+
     // Configure Polymarket data source
     // Request delay of 100ms between paginated requests
-    let source = PolymarketClient::new(config.polymarket_private_key, 100);
+    let source = match config.polymarket.private_key {
+        Some(private_key) => PolymarketClient::new(private_key, 100),
+        None => PolymarketClient::public(100),
+    }
+    .checkpoint(checkpoint);
 
     // Start the data pipeline
     let handles = TickflowBuilder::new(source, database)
@@ -1,9 +1,27 @@
+//! Example: Load Yahoo Finance statements, price history and calendars into PostgreSQL.
+//!
+//! Proxies are read from the file in `PROXIES_PATH`, one per line; without it Yahoo is
+//! fetched directly.
+//!
+//! The last completed symbol is checkpointed under `.checkpoints/`, so an interrupted
+//! run resumes with the next symbol. Pass `--restart` to start from the first symbol.
+//!
+//! Run with:
+//! ```bash
+//! cargo run --example yahoo_to_postgres --features yahoo,postgres
+//! ```
+
 #[cfg(all(feature = "yahoo", feature = "postgres"))]
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    use std::sync::Arc;
     use tickflow::{
         config::AppConfig,
-        connectors::yahoo::ProxyYahooClient,
+        connectors::yahoo::{
+            BarInterval, ClientSelection, ErrorPolicy, StatementFrequency, YahooClient,
+            symbols::load_proxies,
+        },
+        core::{Checkpoint, FileCheckpointStore},
         pipeline::TickflowBuilder,
         storage::{Database, postgres_handler::yahoo::YahooMessageHandler},
     };
@@ -16,22 +34,41 @@ async fn main() -> anyhow::Result<()> {
 
     // Load environment variables
     dotenvy::dotenv().ok();
-    let config = AppConfig::from_env()?;
+    let config = AppConfig::load()?;
 
     // Setup database connection and schema
-    let database = Database::connect(&config.database_url, YahooMessageHandler).await?;
+    let database = Database::connect(config.database_url()?, YahooMessageHandler).await?;
     database.initialize_schema().await?;
 
     // Configure data source
     let symbols = ["PLTR", "AAPL"].iter().map(|s| s.to_string()).collect();
-    let proxies = [
-        "r2VGXNT8iGmOeYi:ofOwRXeEm9pQgO7@212.32.123.187:43160",
-        "AvtZPuXpe7yV7xA:k3toqiMZudQeXS7@207.135.202.204:46128",
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect();
-    let source = ProxyYahooClient::new(proxies, symbols, 2000_u64)?;
+    let proxies = match config.yahoo.proxies_path {
+        Some(path) => load_proxies(path).await?,
+        None => Vec::new(),
+    };
+    let checkpoint = Checkpoint::new(
+        Arc::new(FileCheckpointStore::new(".checkpoints")),
+        "yahoo_statements",
+    );
+    if std::env::args().any(|arg| arg == "--restart") {
+        checkpoint.clear().await?;
+    }
+    // Five years of daily bars, dividends and splits
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::days(5 * 365);
+    // One request every 2s per proxy, with a symbol in flight per proxy
+    let source = YahooClient::new(symbols, 2000_u64)
+        .proxies(proxies)?
+        .frequencies(vec![
+            StatementFrequency::Quarterly,
+            StatementFrequency::Annual,
+            StatementFrequency::Ttm,
+        ])
+        .history(start, end, BarInterval::Day)
+        .selection(ClientSelection::HealthWeighted)
+        .error_policy(ErrorPolicy::Retry(2))
+        .concurrency(3)
+        .checkpoint(checkpoint);
 
     // Start the data pipeline
     let handles = TickflowBuilder::new(source, database)
@@ -0,0 +1,312 @@
+//! Implementations of the CLI subcommands.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use tickflow::config::{AppConfig, PipelineConfig};
+use tickflow::connectors::alpaca::{ReplaySource, ReplaySpeed};
+use tickflow::pipeline::{PipelineStatus, StatusRegistry, Supervisor, TickflowBuilder};
+use tickflow::storage::postgres::{AlpacaMessageHandler, DatabaseMessageHandler};
+use tickflow::storage::{Database, PostgresCheckpointStore};
+use tokio_postgres::{Client, NoTls};
+use tracing::{error, info};
+
+use crate::{Failure, pipelines};
+
+/// Supervises the named pipelines, or all configured ones, until they stop.
+///
+/// The database is checked up front when a pipeline writes to it. With `health_addr`
+/// set, the health server reports on them while they run.
+pub async fn run(mut config: AppConfig, names: Vec<String>) -> Result<(), Failure> {
+    if config.pipelines.is_empty() {
+        config
+            .pipelines
+            .insert("alpaca".to_string(), default_pipeline());
+    }
+    let names = if names.is_empty() {
+        config.pipelines.keys().cloned().collect()
+    } else {
+        names
+    };
+
+    // Validate everything up front so all problems surface before anything starts
+    let problems: Vec<String> = names
+        .iter()
+        .filter_map(|name| config.pipeline(name).err())
+        .map(|err| format!("{err:#}"))
+        .collect();
+    if !problems.is_empty() {
+        return Err(Failure::Config(anyhow!(problems.join("\n"))));
+    }
+    // An unreachable database is reported as such instead of as a failed pipeline
+    if names
+        .iter()
+        .any(|name| config.pipelines[name].sink == "postgres")
+    {
+        connect(&config).await?;
+    }
+
+    let registry = StatusRegistry::new();
+    if let Some(addr) = &config.health_addr {
+        serve_health(addr, registry.clone()).await?;
+    }
+
+    let config = Arc::new(config);
+    let mut supervisor = Supervisor::new().status(registry.clone());
+    for name in names {
+        let policy = config.pipelines[&name].restart.clone();
+        let config = config.clone();
+        let pipeline = name.clone();
+        let status = registry.pipeline(&name);
+        supervisor = supervisor.pipeline(&name, policy, move || {
+            pipelines::run(config.clone(), pipeline.clone(), status.clone())
+        });
+    }
+    supervisor.run().await.map_err(Failure::Runtime)
+}
+
+/// Command line settings of a backfill.
+pub struct BackfillOptions {
+    pub symbols_file: Option<PathBuf>,
+    pub history_days: Option<u64>,
+    pub sink: String,
+    pub path: Option<String>,
+    /// Start over instead of resuming an interrupted backfill
+    pub restart: bool,
+}
+
+/// Runs a one-off pipeline from `source` into `sink`, resuming where an interrupted
+/// backfill of the same source stopped unless `restart` is set.
+pub async fn backfill(
+    mut config: AppConfig,
+    source: &str,
+    options: BackfillOptions,
+) -> Result<(), Failure> {
+    let BackfillOptions {
+        symbols_file,
+        history_days,
+        sink,
+        path,
+        restart,
+    } = options;
+    if let Some(file) = symbols_file {
+        config.symbols_path = Some(file.display().to_string());
+    }
+    let mut options = toml::Table::new();
+    if let Some(days) = history_days {
+        options.insert("history_days".to_string(), (days as i64).into());
+    }
+    if let Some(path) = path {
+        options.insert("path".to_string(), path.into());
+    }
+
+    let name = format!("{source}-backfill");
+    let postgres = sink == "postgres";
+    config.pipelines.insert(
+        name.clone(),
+        PipelineConfig {
+            source: source.to_string(),
+            sink,
+            channel_capacity: None,
+            symbols: Vec::new(),
+            options,
+            restart: Default::default(),
+            max_sink_errors: None,
+            schedule: None,
+            overflow: Default::default(),
+        },
+    );
+    let pipeline = config.pipeline(&name).map_err(Failure::Config)?;
+    if postgres {
+        connect(&config).await?;
+    }
+    if restart {
+        let checkpoint = pipelines::checkpoint(&config, &name, pipeline)
+            .await
+            .map_err(Failure::Runtime)?;
+        checkpoint.clear().await.map_err(Failure::Runtime)?;
+        info!(pipeline = %name, "Discarded the backfill checkpoint");
+    }
+
+    let status = PipelineStatus::new(&name);
+    pipelines::run(Arc::new(config), name, status)
+        .await
+        .map_err(Failure::Runtime)
+}
+
+/// Creates the tables of every compiled-in connector, plus the checkpoint table.
+pub async fn migrate(config: &AppConfig) -> Result<(), Failure> {
+    let client = connect(config).await?;
+
+    let mut migrations = vec![(
+        "alpaca",
+        AlpacaMessageHandler.initialize_schema(client.clone()),
+    )];
+    #[cfg(feature = "yahoo")]
+    migrations.push((
+        "yahoo",
+        tickflow::storage::postgres_handler::yahoo::YahooMessageHandler
+            .initialize_schema(client.clone()),
+    ));
+    #[cfg(feature = "polymarket")]
+    migrations.push((
+        "polymarket",
+        tickflow::storage::postgres_handler::polymarket::PolymarketMessageHandler
+            .initialize_schema(client.clone()),
+    ));
+
+    for (connector, migration) in migrations {
+        migration
+            .await
+            .map_err(|err| Failure::Runtime(anyhow!("{connector} schema: {err}")))?;
+        info!(connector, "Schema up to date");
+    }
+    PostgresCheckpointStore::new(client)
+        .initialize_schema()
+        .await
+        .map_err(|err| Failure::Runtime(anyhow!("checkpoint schema: {err}")))?;
+    Ok(())
+}
+
+/// Feeds a recorded Alpaca session into PostgreSQL.
+pub async fn replay(
+    config: &AppConfig,
+    capture_file: PathBuf,
+    speed: Option<f64>,
+) -> Result<(), Failure> {
+    let url = config.database_url().map_err(Failure::Config)?;
+    let database = Database::connect(url, AlpacaMessageHandler)
+        .await
+        .map_err(|err| Failure::Database(err.into()))?;
+    database
+        .initialize_schema()
+        .await
+        .map_err(|err| Failure::Runtime(err.into()))?;
+
+    let speed = match speed {
+        Some(multiplier) => ReplaySpeed::Paced { multiplier },
+        None => ReplaySpeed::AsFastAsPossible,
+    };
+    let source = ReplaySource::new(capture_file).speed(speed);
+    TickflowBuilder::new(source, database)
+        .channel_capacity(config.channel_capacity)
+        .run()
+        .await
+        .map_err(Failure::Runtime)
+}
+
+/// Validates every pipeline, then lists the row count of each table in the database.
+///
+/// Database checks still run when the configuration is invalid, so one invocation
+/// reports everything.
+pub async fn check(config: &AppConfig) -> Result<(), Failure> {
+    let problems: Vec<String> = config
+        .pipelines
+        .keys()
+        .filter_map(|name| config.pipeline(name).err())
+        .map(|err| format!("{err:#}"))
+        .collect();
+    if problems.is_empty() {
+        println!("config: ok ({} pipelines)", config.pipelines.len());
+    } else {
+        println!("config: invalid");
+        for problem in &problems {
+            println!("{problem}");
+        }
+    }
+
+    if config.database_url.is_some() {
+        let client = connect(config).await?;
+        println!("database: connected");
+        for (table, rows) in row_counts(&client)
+            .await
+            .map_err(|err| Failure::Database(err.into()))?
+        {
+            println!("  {table:<32} {rows:>12}");
+        }
+    } else {
+        println!("database: skipped, DATABASE_URL is not set");
+    }
+
+    if !problems.is_empty() {
+        return Err(Failure::Config(anyhow!(
+            "{} configuration problem(s)",
+            problems.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Starts the health server in the background.
+#[cfg(feature = "health")]
+async fn serve_health(addr: &str, registry: StatusRegistry) -> Result<(), Failure> {
+    let server = tickflow::pipeline::HealthServer::bind(addr, registry)
+        .await
+        .map_err(|err| Failure::Config(anyhow!("health_addr {addr:?}: {err:#}")))?;
+    tokio::spawn(async move {
+        if let Err(err) = server.serve().await {
+            error!("Health server failed: {err:#}");
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "health"))]
+async fn serve_health(_addr: &str, _registry: StatusRegistry) -> Result<(), Failure> {
+    Err(Failure::Config(anyhow!(
+        "health_addr needs the health feature"
+    )))
+}
+
+/// The pipeline this binary ran before it became config driven.
+fn default_pipeline() -> PipelineConfig {
+    let mut options = toml::Table::new();
+    options.insert("quotes".to_string(), vec!["ETH/USD".to_string()].into());
+    PipelineConfig {
+        source: "alpaca".to_string(),
+        sink: "postgres".to_string(),
+        channel_capacity: None,
+        symbols: Vec::new(),
+        options,
+        restart: Default::default(),
+        max_sink_errors: None,
+        schedule: None,
+        overflow: Default::default(),
+    }
+}
+
+async fn connect(config: &AppConfig) -> Result<Arc<Client>, Failure> {
+    let url = config.database_url().map_err(Failure::Config)?;
+    let (client, connection) = tokio_postgres::connect(url, NoTls)
+        .await
+        .map_err(|err| Failure::Database(anyhow!("failed to connect to the database: {err}")))?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            error!("Database connection error: {err}");
+        }
+    });
+    Ok(Arc::new(client))
+}
+
+async fn row_counts(client: &Client) -> Result<Vec<(String, i64)>, tokio_postgres::Error> {
+    let tables = client
+        .query(
+            "SELECT table_name::TEXT FROM information_schema.tables \
+             WHERE table_schema = 'public' AND table_type = 'BASE TABLE' \
+             ORDER BY table_name",
+            &[],
+        )
+        .await?;
+
+    let mut counts = Vec::with_capacity(tables.len());
+    for row in tables {
+        let table: String = row.get(0);
+        let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+        let count = client
+            .query_one(&format!("SELECT COUNT(*) FROM {quoted}"), &[])
+            .await?;
+        counts.push((table, count.get(0)));
+    }
+    Ok(counts)
+}
@@ -0,0 +1,157 @@
+//! Tickflow CLI entrypoint.
+//!
+//! `tickflow run` supervises the configured pipelines (see `AppConfig::load`); the other
+//! subcommands cover one-off operations. Exit codes are stable so the binary can run
+//! under cron or systemd:
+//!
+//! | code | meaning |
+//! |------|---------|
+//! | 0 | success |
+//! | 1 | a pipeline or operation failed at runtime |
+//! | 2 | invalid command line |
+//! | 3 | invalid configuration |
+//! | 4 | database unreachable |
+
+mod commands;
+mod pipelines;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use tickflow::config::AppConfig;
+use tracing::error;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser)]
+#[command(name = "tickflow", version, about = "Market data pipelines")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the configured pipelines (the default without a subcommand)
+    Run {
+        /// Pipelines to run; all configured pipelines when omitted
+        pipelines: Vec<String>,
+    },
+    /// Fetch historical data for a list of symbols once
+    Backfill {
+        #[arg(long, value_enum)]
+        source: BackfillSource,
+        /// CSV file with one symbol per line in the first column
+        #[arg(long)]
+        symbols_file: Option<PathBuf>,
+        /// Also fetch this many days of daily price history (Yahoo only)
+        #[arg(long)]
+        history_days: Option<u64>,
+        #[arg(long, default_value = "postgres")]
+        sink: String,
+        /// Output directory for the file sink
+        #[arg(long)]
+        path: Option<String>,
+        /// Discard the checkpoint of an interrupted backfill and start from the beginning
+        #[arg(long)]
+        restart: bool,
+    },
+    /// Create or upgrade every table Tickflow writes to
+    Migrate,
+    /// Replay a recorded Alpaca session into PostgreSQL
+    Replay {
+        capture_file: PathBuf,
+        /// Reproduce the recorded pace, sped up by this factor; as fast as possible when omitted
+        #[arg(long)]
+        speed: Option<f64>,
+    },
+    /// Validate the configuration, test the database and list table row counts
+    Check,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BackfillSource {
+    Yahoo,
+    Polymarket,
+}
+
+/// A failed command, classified by the exit code it maps to.
+enum Failure {
+    Runtime(anyhow::Error),
+    Config(anyhow::Error),
+    Database(anyhow::Error),
+}
+
+impl Failure {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Failure::Runtime(_) => ExitCode::from(1),
+            Failure::Config(_) => ExitCode::from(3),
+            Failure::Database(_) => ExitCode::from(4),
+        }
+    }
+
+    fn error(&self) -> &anyhow::Error {
+        match self {
+            Failure::Runtime(err) | Failure::Config(err) | Failure::Database(err) => err,
+        }
+    }
+}
+
+/// Parses the command line, runs the subcommand and maps its outcome to an exit code.
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // RUST_LOG controls verbosity, e.g. `RUST_LOG=info,tickflow::connectors=debug`
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+    dotenvy::dotenv().ok();
+
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => return report(Failure::Config(err)),
+    };
+
+    let result = match cli.command.unwrap_or(Command::Run {
+        pipelines: Vec::new(),
+    }) {
+        Command::Run { pipelines } => commands::run(config, pipelines).await,
+        Command::Backfill {
+            source,
+            symbols_file,
+            history_days,
+            sink,
+            path,
+            restart,
+        } => {
+            let source = match source {
+                BackfillSource::Yahoo => "yahoo",
+                BackfillSource::Polymarket => "polymarket",
+            };
+            let options = commands::BackfillOptions {
+                symbols_file,
+                history_days,
+                sink,
+                path,
+                restart,
+            };
+            commands::backfill(config, source, options).await
+        }
+        Command::Migrate => commands::migrate(&config).await,
+        Command::Replay {
+            capture_file,
+            speed,
+        } => commands::replay(&config, capture_file, speed).await,
+        Command::Check => commands::check(&config).await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => report(failure),
+    }
+}
+
+fn report(failure: Failure) -> ExitCode {
+    error!("{:#}", failure.error());
+    failure.exit_code()
+}
@@ -0,0 +1,445 @@
+//! Builds sources and sinks for a configured pipeline and runs them to completion.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tickflow::config::{AppConfig, Overflow, PipelineConfig};
+use tickflow::connectors::alpaca::{AlpacaMessage, AlpacaWebSocketClient};
+use tickflow::core::{Checkpoint, CheckpointStore, FileCheckpointStore};
+use tickflow::pipeline::{OverflowPolicy, PipelineStatus};
+use tickflow::prelude::*;
+use tickflow::storage::postgres::{AlpacaMessageHandler, DatabaseMessageHandler};
+use tickflow::storage::{Database, PostgresCheckpointStore};
+use tokio_postgres::NoTls;
+use tracing::{error, info};
+
+/// Directory holding the checkpoints of pipelines that do not write to PostgreSQL.
+const CHECKPOINT_DIR: &str = ".checkpoints";
+
+/// Builds the named pipeline from `config` and runs it until its source stops, keeping
+/// `status` up to date.
+pub async fn run(config: Arc<AppConfig>, name: String, status: PipelineStatus) -> Result<()> {
+    let pipeline = config.pipeline(&name)?;
+    match pipeline.source.as_str() {
+        "alpaca" => {
+            let alpaca = config.alpaca()?;
+            let bars = strings(pipeline, "bars")?;
+            let mut quotes = strings(pipeline, "quotes")?;
+            let trades = strings(pipeline, "trades")?;
+            if bars.is_empty() && quotes.is_empty() && trades.is_empty() {
+                quotes = pipeline.symbols.clone();
+            }
+            let source = AlpacaWebSocketClient::new(
+                &alpaca.ws_url,
+                alpaca.api_key.expose(),
+                alpaca.api_secret.expose(),
+                &as_strs(&bars),
+                &as_strs(&quotes),
+                &as_strs(&trades),
+            )
+            .status(status.clone());
+            let overflow = match pipeline.overflow.policy {
+                Overflow::ConflateLatest => {
+                    OverflowPolicy::conflate_latest(AlpacaMessage::conflation_key)
+                }
+                _ => overflow(pipeline)?,
+            };
+            drain(
+                &config,
+                &name,
+                pipeline,
+                &status,
+                source,
+                AlpacaMessageHandler,
+                overflow,
+            )
+            .await
+        }
+        #[cfg(feature = "yahoo")]
+        "yahoo" => yahoo::run(&config, &name, pipeline, &status).await,
+        #[cfg(feature = "polymarket")]
+        "polymarket" => polymarket::run(&config, &name, pipeline, &status).await,
+        other => Err(anyhow!(
+            "source {other:?} is not available in this build, enable its feature"
+        )),
+    }
+}
+
+/// Connects `source` to the pipeline's sink and runs both until the source stops.
+async fn drain<M, Src, H>(
+    config: &AppConfig,
+    name: &str,
+    pipeline: &PipelineConfig,
+    status: &PipelineStatus,
+    source: Src,
+    handler: H,
+    overflow: OverflowPolicy<M>,
+) -> Result<()>
+where
+    M: Message + Serialize + DeserializeOwned,
+    Src: MessageSource<M>,
+    H: DatabaseMessageHandler<M>,
+{
+    let capacity = config.channel_capacity_for(pipeline);
+    info!(pipeline = name, source = %pipeline.source, sink = %pipeline.sink, "Running pipeline");
+    match pipeline.sink.as_str() {
+        "postgres" => {
+            let database = Database::connect(config.database_url()?, handler).await?;
+            database.initialize_schema().await?;
+            let builder = TickflowBuilder::new(source, database)
+                .channel_capacity(capacity)
+                .status(status.clone())
+                .overflow(overflow);
+            sink_errors(builder, pipeline).run().await
+        }
+        #[cfg(feature = "file")]
+        "file" => {
+            let sink = file_sink::<M>(name, pipeline)?;
+            let builder = TickflowBuilder::new(source, sink)
+                .channel_capacity(capacity)
+                .status(status.clone())
+                .overflow(overflow);
+            sink_errors(builder, pipeline).run().await
+        }
+        other => Err(anyhow!(
+            "sink {other:?} is not available in this build, enable its feature"
+        )),
+    }
+}
+
+/// Runs a batch source once, or on the pipeline's schedule with a fresh source per run.
+async fn drain_batch<M, Src, H, F>(
+    config: &AppConfig,
+    name: &str,
+    pipeline: &PipelineConfig,
+    status: &PipelineStatus,
+    mut factory: F,
+    handler: H,
+) -> Result<()>
+where
+    M: Message + Serialize + DeserializeOwned,
+    Src: MessageSource<M>,
+    H: DatabaseMessageHandler<M>,
+    F: FnMut() -> Result<Src> + Send + 'static,
+{
+    #[cfg(feature = "scheduler")]
+    if let Some(schedule) = &pipeline.schedule {
+        use tickflow::pipeline::{Schedule, ScheduledSource};
+
+        let source =
+            ScheduledSource::new(Schedule::from_config(schedule)?, factory).status(status.clone());
+        return drain(
+            config,
+            name,
+            pipeline,
+            status,
+            source,
+            handler,
+            overflow(pipeline)?,
+        )
+        .await;
+    }
+    let source = factory()?;
+    drain(
+        config,
+        name,
+        pipeline,
+        status,
+        source,
+        handler,
+        overflow(pipeline)?,
+    )
+    .await
+}
+
+/// Resume position of a batch pipeline, keyed by its name: in `tickflow_checkpoints`
+/// when the pipeline writes to PostgreSQL, under `.checkpoints/` otherwise.
+pub async fn checkpoint(
+    config: &AppConfig,
+    name: &str,
+    pipeline: &PipelineConfig,
+) -> Result<Checkpoint> {
+    let store: Arc<dyn CheckpointStore> = match pipeline.sink.as_str() {
+        "postgres" => {
+            let (client, connection) = tokio_postgres::connect(config.database_url()?, NoTls)
+                .await
+                .context("failed to connect to the checkpoint database")?;
+            tokio::spawn(async move {
+                if let Err(err) = connection.await {
+                    error!("Checkpoint database connection error: {err}");
+                }
+            });
+            let store = PostgresCheckpointStore::new(Arc::new(client));
+            store.initialize_schema().await?;
+            Arc::new(store)
+        }
+        _ => Arc::new(FileCheckpointStore::new(CHECKPOINT_DIR)),
+    };
+    Ok(Checkpoint::new(store, name))
+}
+
+/// Stops a checkpointed pipeline on the first batch its sink fails unless
+/// `max_sink_errors` is set, so the checkpoint never moves past an unwritten batch.
+fn checkpointed(pipeline: &PipelineConfig) -> PipelineConfig {
+    let mut pipeline = pipeline.clone();
+    pipeline.max_sink_errors.get_or_insert(1);
+    pipeline
+}
+
+/// Applies the pipeline's `max_sink_errors`, if set.
+fn sink_errors<M, Src, Sink>(
+    builder: TickflowBuilder<M, Src, Sink>,
+    pipeline: &PipelineConfig,
+) -> TickflowBuilder<M, Src, Sink>
+where
+    M: Message,
+    Src: MessageSource<M>,
+    Sink: MessageSink<M>,
+{
+    match pipeline.max_sink_errors {
+        Some(errors) => builder.max_sink_errors(errors),
+        None => builder,
+    }
+}
+
+/// The pipeline's overflow policy, for policies that do not depend on the message type.
+fn overflow<M>(pipeline: &PipelineConfig) -> Result<OverflowPolicy<M>>
+where
+    M: Serialize + DeserializeOwned,
+{
+    let config = &pipeline.overflow;
+    match config.policy {
+        Overflow::Block => Ok(OverflowPolicy::Block),
+        Overflow::DropOldest => Ok(OverflowPolicy::DropOldest),
+        Overflow::DropNewest => Ok(OverflowPolicy::DropNewest),
+        Overflow::ConflateLatest => Err(anyhow!(
+            "conflate_latest is not supported for {} sources",
+            pipeline.source
+        )),
+        #[cfg(feature = "file")]
+        Overflow::SpillToDisk => {
+            let path = config
+                .spill_path
+                .as_ref()
+                .ok_or_else(|| anyhow!("spill_to_disk needs overflow.spill_path"))?;
+            Ok(OverflowPolicy::spill_to_disk(path))
+        }
+        #[cfg(not(feature = "file"))]
+        Overflow::SpillToDisk => Err(anyhow!("spill_to_disk needs the file feature")),
+    }
+}
+
+/// File sink writing to `options.path`, prefixed with the pipeline name.
+#[cfg(feature = "file")]
+fn file_sink<M>(name: &str, pipeline: &PipelineConfig) -> Result<tickflow::storage::FileSink<M>> {
+    use tickflow::storage::{FileFormat, FileSink};
+
+    let dir = string(pipeline, "path")?.ok_or_else(|| anyhow!("file sink needs options.path"))?;
+    let format = match string(pipeline, "format")?.as_deref() {
+        None | Some("jsonl") => FileFormat::JsonLines,
+        Some("csv") => FileFormat::Csv,
+        Some(other) => {
+            return Err(anyhow!(
+                "unknown file format {other:?}, expected jsonl or csv"
+            ));
+        }
+    };
+    let gzip = pipeline
+        .options
+        .get("gzip")
+        .map(|value| {
+            value
+                .as_bool()
+                .ok_or_else(|| anyhow!("options.gzip must be a boolean"))
+        })
+        .transpose()?
+        .unwrap_or(false);
+    Ok(FileSink::new(dir, format).prefix(name).gzip(gzip))
+}
+
+#[cfg(feature = "yahoo")]
+mod yahoo {
+    use anyhow::{Result, anyhow};
+    use tickflow::config::{AppConfig, PipelineConfig};
+    use tickflow::connectors::yahoo::symbols::{load_proxies, load_symbols};
+    use tickflow::connectors::yahoo::{BarInterval, StatementFrequency, YahooClient};
+    use tickflow::pipeline::PipelineStatus;
+    use tickflow::storage::postgres_handler::yahoo::YahooMessageHandler;
+
+    use super::{checkpoint, checkpointed, drain_batch, integer, strings};
+
+    /// Fetches statements (and optionally daily history) for the pipeline's symbols,
+    /// resuming after the last symbol an interrupted run completed.
+    pub async fn run(
+        config: &AppConfig,
+        name: &str,
+        pipeline: &PipelineConfig,
+        status: &PipelineStatus,
+    ) -> Result<()> {
+        let symbols = if pipeline.symbols.is_empty() {
+            load_symbols(config.symbols_path()?.to_string()).await?
+        } else {
+            pipeline.symbols.clone()
+        };
+        let proxies = match &config.yahoo.proxies_path {
+            Some(path) => load_proxies(path.clone()).await?,
+            None => Vec::new(),
+        };
+
+        let frequencies = strings(pipeline, "frequencies")?
+            .iter()
+            .map(|frequency| match frequency.as_str() {
+                "quarterly" => Ok(StatementFrequency::Quarterly),
+                "annual" => Ok(StatementFrequency::Annual),
+                "ttm" => Ok(StatementFrequency::Ttm),
+                other => Err(anyhow!("unknown statement frequency {other:?}")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let interval_ms = integer(pipeline, "request_interval_ms")?.unwrap_or(2000);
+        let concurrency = integer(pipeline, "concurrency")?;
+        let history_days = integer(pipeline, "history_days")?;
+        let checkpoint = checkpoint(config, name, pipeline).await?;
+        let factory = move || {
+            let mut source = YahooClient::new(symbols.clone(), interval_ms)
+                .proxies(proxies.clone())?
+                .checkpoint(checkpoint.clone());
+            if !frequencies.is_empty() {
+                source = source.frequencies(frequencies.clone());
+            }
+            if let Some(concurrency) = concurrency {
+                source = source.concurrency(concurrency as usize);
+            }
+            // The history window ends at the start of each run
+            if let Some(days) = history_days {
+                let end = chrono::Utc::now();
+                let start = end - chrono::Duration::days(days as i64);
+                source = source.history(start, end, BarInterval::Day);
+            }
+            Ok(source)
+        };
+
+        let pipeline = &checkpointed(pipeline);
+        drain_batch(config, name, pipeline, status, factory, YahooMessageHandler).await
+    }
+}
+
+#[cfg(feature = "polymarket")]
+mod polymarket {
+    use anyhow::{Result, anyhow};
+    use tickflow::config::{AppConfig, PipelineConfig};
+    use tickflow::connectors::polymarket::{PolymarketClient, PolymarketMarketSync};
+    use tickflow::pipeline::PipelineStatus;
+    use tickflow::storage::Database;
+    use tickflow::storage::postgres_handler::polymarket::{
+        PolymarketMessageHandler, market_loader,
+    };
+    use tokio::time::Duration;
+
+    use super::{checkpoint, checkpointed, drain, drain_batch, integer, overflow, string};
+
+    /// Fetches every market once (`mode = "markets"`), resuming from the last saved page,
+    /// or keeps them in sync (`mode = "sync"`).
+    pub async fn run(
+        config: &AppConfig,
+        name: &str,
+        pipeline: &PipelineConfig,
+        status: &PipelineStatus,
+    ) -> Result<()> {
+        let delay_ms = integer(pipeline, "request_delay_ms")?.unwrap_or(100);
+        let private_key = config.polymarket.private_key.clone();
+        let connect = move || match &private_key {
+            Some(private_key) => PolymarketClient::new(private_key.clone(), delay_ms),
+            None => PolymarketClient::public(delay_ms),
+        };
+
+        match string(pipeline, "mode")?.as_deref() {
+            None | Some("markets") => {
+                let checkpoint = checkpoint(config, name, pipeline).await?;
+                let factory = move || Ok(connect().checkpoint(checkpoint.clone()));
+                let pipeline = &checkpointed(pipeline);
+                drain_batch(
+                    config,
+                    name,
+                    pipeline,
+                    status,
+                    factory,
+                    PolymarketMessageHandler,
+                )
+                .await
+            }
+            Some("sync") => {
+                let client = connect();
+                let interval = integer(pipeline, "interval_secs")?.unwrap_or(15 * 60);
+                let mut source = PolymarketMarketSync::new(client, Duration::from_secs(interval));
+                // Diff against the stored markets when writing back to Postgres
+                if pipeline.sink == "postgres" {
+                    let database =
+                        Database::connect(config.database_url()?, PolymarketMessageHandler).await?;
+                    database.initialize_schema().await?;
+                    source = source.reload_baseline(market_loader(database.client()));
+                }
+                drain(
+                    config,
+                    name,
+                    pipeline,
+                    status,
+                    source,
+                    PolymarketMessageHandler,
+                    overflow(pipeline)?,
+                )
+                .await
+            }
+            Some(other) => Err(anyhow!(
+                "unknown polymarket mode {other:?}, expected markets or sync"
+            )),
+        }
+    }
+}
+
+fn string(pipeline: &PipelineConfig, key: &str) -> Result<Option<String>> {
+    pipeline
+        .options
+        .get(key)
+        .map(|value| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("options.{key} must be a string"))
+        })
+        .transpose()
+}
+
+fn strings(pipeline: &PipelineConfig, key: &str) -> Result<Vec<String>> {
+    let Some(value) = pipeline.options.get(key) else {
+        return Ok(Vec::new());
+    };
+    value
+        .as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .ok_or_else(|| anyhow!("options.{key} must be a list of strings"))
+}
+
+fn integer(pipeline: &PipelineConfig, key: &str) -> Result<Option<u64>> {
+    pipeline
+        .options
+        .get(key)
+        .map(|value| {
+            value
+                .as_integer()
+                .and_then(|int| u64::try_from(int).ok())
+                .ok_or_else(|| anyhow!("options.{key} must be a non-negative integer"))
+        })
+        .transpose()
+}
+
+fn as_strs(values: &[String]) -> Vec<&str> {
+    values.iter().map(String::as_str).collect()
+}
@@ -1,67 +1,358 @@
 //! Application configuration helpers.
-use anyhow::{Result, anyhow};
+//!
+//! Settings are layered: an optional TOML file is read first and environment variables
+//! override it. Connector sections are only validated when a pipeline uses them, so a
+//! Yahoo-only deployment does not need Alpaca credentials.
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::env;
+use std::path::Path;
 
-/// Aggregated configuration required to run the Tickflow binary.
+use crate::pipeline::RestartPolicy;
+use crate::secret::Secret;
+
+/// Environment variable naming the configuration file.
+pub const CONFIG_PATH_VAR: &str = "TICKFLOW_CONFIG";
+/// File read when `TICKFLOW_CONFIG` is unset, if it exists.
+pub const DEFAULT_CONFIG_PATH: &str = "tickflow.toml";
+
+const SOURCES: [&str; 3] = ["alpaca", "yahoo", "polymarket"];
+const SINKS: [&str; 2] = ["postgres", "file"];
+
+/// Aggregated configuration required to run Tickflow pipelines.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AppConfig {
-    pub database_url: String,
-    pub alpaca_api_key: String,
-    pub alpaca_api_secret: String,
-    pub alpaca_ws_url: String,
+    /// May embed a password, so it is kept as a secret
+    pub database_url: Option<Secret>,
     pub channel_capacity: usize,
-    pub symbols_path: String,
-    pub polymarket_private_key: String,
+    pub symbols_path: Option<String>,
+    pub alpaca: AlpacaConfig,
+    pub yahoo: YahooConfig,
+    pub polymarket: PolymarketConfig,
+    /// Address the health server listens on, e.g. `0.0.0.0:8080`; disabled when unset
+    pub health_addr: Option<String>,
+    /// Named pipelines, keyed by the name used to run them.
+    pub pipelines: BTreeMap<String, PipelineConfig>,
+}
+
+/// Alpaca credentials; required only by pipelines with an `alpaca` source.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlpacaConfig {
+    pub api_key: Option<Secret>,
+    pub api_secret: Option<Secret>,
+    pub ws_url: Option<String>,
+}
+
+/// Validated Alpaca credentials.
+#[derive(Debug, Clone)]
+pub struct AlpacaCredentials {
+    pub api_key: Secret,
+    pub api_secret: Secret,
+    pub ws_url: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct YahooConfig {
+    /// File listing Yahoo proxies, one per line; Yahoo is fetched directly when unset.
+    pub proxies_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolymarketConfig {
+    /// Only needed for authenticated Polymarket endpoints; market data is public.
+    pub private_key: Option<Secret>,
+}
+
+/// A named source-to-sink pipeline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// One of `alpaca`, `yahoo` or `polymarket`
+    pub source: String,
+    /// One of `postgres` or `file`
+    pub sink: String,
+    /// Overrides the top-level `channel_capacity`
+    pub channel_capacity: Option<usize>,
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Connector-specific settings passed through as-is
+    #[serde(default)]
+    pub options: toml::Table,
+    /// What the supervisor does when the pipeline stops
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Consecutive batches the sink may fail before the pipeline stops with an error,
+    /// which `on_failure` restarts; sink errors are only logged when unset
+    pub max_sink_errors: Option<u32>,
+    /// Re-runs a batch source on a schedule instead of once
+    pub schedule: Option<ScheduleConfig>,
+    /// What happens to new batches while the channel is full
+    #[serde(default)]
+    pub overflow: OverflowConfig,
+}
+
+/// Overflow policy of a pipeline; see `OverflowPolicy`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverflowConfig {
+    pub policy: Overflow,
+    /// File batches are spilled to; required by `spill_to_disk`
+    pub spill_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    #[default]
+    Block,
+    DropOldest,
+    DropNewest,
+    /// Latest quote or bar per symbol; Alpaca sources only
+    ConflateLatest,
+    SpillToDisk,
+}
+
+/// When a scheduled pipeline runs; exactly one of the kinds must be set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    pub interval_secs: Option<u64>,
+    /// Five-field cron expression, e.g. `"0 6 * * 1-5"`
+    pub cron: Option<String>,
+    /// Run this many minutes after the US equity close, on trading days only
+    pub after_market_close_mins: Option<u64>,
+    /// IANA time zone the cron expression is evaluated in; UTC when unset
+    pub timezone: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            database_url: None,
+            channel_capacity: 1_000,
+            symbols_path: None,
+            alpaca: AlpacaConfig::default(),
+            yahoo: YahooConfig::default(),
+            polymarket: PolymarketConfig::default(),
+            health_addr: None,
+            pipelines: BTreeMap::new(),
+        }
+    }
 }
 
 impl AppConfig {
-    /// Builds an `AppConfig` by reading the expected environment variables.
+    /// Builds an `AppConfig` from the environment alone.
     ///
     /// Provides defaults where reasonable (e.g. channel size) and returns an error when
-    /// a required variable is missing or malformed.
+    /// a variable is malformed. Missing settings are reported by the accessors.
     pub fn from_env() -> Result<Self> {
-        let channel_capacity = env::var("DATAFEED_CHANNEL_SIZE")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(1_000);
-
-        let database_url = match env::var("DATABASE_URL") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("DATABASE_URL must be set")),
-        };
+        Self::default().with_overrides(|key| env::var(key).ok())
+    }
 
-        let alpaca_api_key = match env::var("APCA_API_KEY_ID") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("APCA_API_KEY_ID must be set")),
+    /// Reads the file named by `TICKFLOW_CONFIG` (or `tickflow.toml` when present) and
+    /// applies environment overrides on top.
+    pub fn load() -> Result<Self> {
+        let config = match env::var(CONFIG_PATH_VAR) {
+            Ok(path) => Self::from_file(path)?,
+            Err(_) if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(DEFAULT_CONFIG_PATH)?
+            }
+            Err(_) => Self::default(),
         };
+        config.with_overrides(|key| env::var(key).ok())
+    }
 
-        let alpaca_api_secret = match env::var("APCA_API_SECRET_KEY") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("APCA_API_SECRET_KEY must be set")),
-        };
+    /// Parses a TOML configuration file without applying environment overrides.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Self::from_toml(&contents)
+            .with_context(|| format!("invalid config file {}", path.display()))
+    }
 
-        let alpaca_ws_url = match env::var("APCA_WS_URL") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("ALPACA_WS_URL must be set")),
-        };
+    /// Parses TOML configuration text.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
 
-        let symbols_path = match env::var("SYMBOLS_PATH") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("SYMBOLS_PATH must be set")),
-        };
+    /// Overrides settings with the variables `lookup` returns; empty values are ignored.
+    ///
+    /// Recognised variables: `DATABASE_URL`, `DATAFEED_CHANNEL_SIZE`, `SYMBOLS_PATH`,
+    /// `APCA_API_KEY_ID`, `APCA_API_SECRET_KEY`, `APCA_WS_URL`, `PROXIES_PATH`, `PK` and
+    /// `HEALTH_ADDR`.
+    /// Credentials can instead be read from the file named by the same variable with a
+    /// `_FILE` suffix, e.g. `APCA_API_SECRET_KEY_FILE=/run/secrets/alpaca`.
+    pub fn with_overrides(mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| lookup(key).filter(|val| !val.is_empty());
 
-        let polymarket_private_key = match env::var("PK") {
-            Ok(val) => val,
-            Err(_) => return Err(anyhow!("PK must be set for Polymarket authentication")),
-        };
+        if let Some(val) = var("DATAFEED_CHANNEL_SIZE") {
+            self.channel_capacity = val
+                .parse()
+                .map_err(|_| anyhow!("DATAFEED_CHANNEL_SIZE must be a number, got {val:?}"))?;
+        }
+        let overrides = [
+            ("SYMBOLS_PATH", &mut self.symbols_path),
+            ("APCA_WS_URL", &mut self.alpaca.ws_url),
+            ("PROXIES_PATH", &mut self.yahoo.proxies_path),
+            ("HEALTH_ADDR", &mut self.health_addr),
+        ];
+        for (key, field) in overrides {
+            if let Some(val) = var(key) {
+                *field = Some(val);
+            }
+        }
+
+        let secrets = [
+            ("DATABASE_URL", &mut self.database_url),
+            ("APCA_API_KEY_ID", &mut self.alpaca.api_key),
+            ("APCA_API_SECRET_KEY", &mut self.alpaca.api_secret),
+            ("PK", &mut self.polymarket.private_key),
+        ];
+        for (key, field) in secrets {
+            if let Some(val) = var(key) {
+                *field = Some(Secret::new(val));
+            } else if let Some(path) = var(&format!("{key}_FILE")) {
+                *field = Some(Secret::from_file(path).with_context(|| format!("{key}_FILE"))?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// The database URL, set via `database_url` or `DATABASE_URL`.
+    pub fn database_url(&self) -> Result<&str> {
+        self.database_url
+            .as_ref()
+            .map(Secret::expose)
+            .ok_or_else(|| anyhow!("DATABASE_URL must be set"))
+    }
+
+    /// The symbols file, set via `symbols_path` or `SYMBOLS_PATH`.
+    pub fn symbols_path(&self) -> Result<&str> {
+        self.symbols_path
+            .as_deref()
+            .ok_or_else(|| anyhow!("SYMBOLS_PATH must be set"))
+    }
 
-        Ok(Self {
-            database_url,
-            alpaca_api_key,
-            alpaca_api_secret,
-            alpaca_ws_url,
-            channel_capacity,
-            symbols_path,
-            polymarket_private_key,
+    /// Alpaca credentials, reporting every missing setting at once.
+    pub fn alpaca(&self) -> Result<AlpacaCredentials> {
+        let mut problems = Vec::new();
+        self.check_alpaca(&mut problems);
+        if !problems.is_empty() {
+            return Err(invalid(problems));
+        }
+
+        let alpaca = self.alpaca.clone();
+        Ok(AlpacaCredentials {
+            api_key: alpaca.api_key.unwrap_or_default(),
+            api_secret: alpaca.api_secret.unwrap_or_default(),
+            ws_url: alpaca.ws_url.unwrap_or_default(),
         })
     }
+
+    /// Looks up a pipeline and validates the settings its source and sink need.
+    ///
+    /// All problems are reported in a single error.
+    pub fn pipeline(&self, name: &str) -> Result<&PipelineConfig> {
+        let pipeline = self.pipelines.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.pipelines.keys().map(String::as_str).collect();
+            anyhow!("unknown pipeline {name:?}, configured: {known:?}")
+        })?;
+
+        let mut problems = Vec::new();
+        match pipeline.source.as_str() {
+            "alpaca" => self.check_alpaca(&mut problems),
+            "yahoo" | "polymarket" => {}
+            other => problems.push(format!(
+                "pipeline {name:?}: unknown source {other:?}, expected one of {SOURCES:?}"
+            )),
+        }
+        match pipeline.sink.as_str() {
+            "postgres" if self.database_url.is_none() => {
+                problems.push("DATABASE_URL must be set".to_string())
+            }
+            "file" if !pipeline.options.contains_key("path") => {
+                problems.push(format!("pipeline {name:?}: file sink needs options.path"))
+            }
+            "postgres" | "file" => {}
+            other => problems.push(format!(
+                "pipeline {name:?}: unknown sink {other:?}, expected one of {SINKS:?}"
+            )),
+        }
+        if pipeline.schedule.is_some() && pipeline.source == "alpaca" {
+            problems.push(format!(
+                "pipeline {name:?}: streaming sources cannot be scheduled"
+            ));
+        }
+        if pipeline.schedule.is_some()
+            && pipeline.source == "polymarket"
+            && pipeline.options.get("mode").and_then(|mode| mode.as_str()) == Some("sync")
+        {
+            problems.push(format!(
+                "pipeline {name:?}: polymarket sync already repeats every interval_secs and cannot be scheduled"
+            ));
+        }
+        #[cfg(feature = "scheduler")]
+        if let Some(schedule) = &pipeline.schedule
+            && let Err(err) = crate::pipeline::Schedule::from_config(schedule)
+        {
+            problems.push(format!("pipeline {name:?}: {err}"));
+        }
+        #[cfg(not(feature = "scheduler"))]
+        if pipeline.schedule.is_some() {
+            problems.push(format!(
+                "pipeline {name:?}: schedules need the scheduler feature"
+            ));
+        }
+        match pipeline.overflow.policy {
+            Overflow::ConflateLatest if pipeline.source != "alpaca" => problems.push(format!(
+                "pipeline {name:?}: conflate_latest is only supported for alpaca sources"
+            )),
+            Overflow::SpillToDisk if pipeline.overflow.spill_path.is_none() => problems.push(
+                format!("pipeline {name:?}: spill_to_disk needs overflow.spill_path"),
+            ),
+            #[cfg(not(feature = "file"))]
+            Overflow::SpillToDisk => problems.push(format!(
+                "pipeline {name:?}: spill_to_disk needs the file feature"
+            )),
+            _ => {}
+        }
+        if pipeline.channel_capacity == Some(0) {
+            problems.push(format!(
+                "pipeline {name:?}: channel_capacity must be positive"
+            ));
+        }
+
+        if !problems.is_empty() {
+            return Err(invalid(problems));
+        }
+        Ok(pipeline)
+    }
+
+    /// Channel capacity for a pipeline, falling back to the top-level setting.
+    pub fn channel_capacity_for(&self, pipeline: &PipelineConfig) -> usize {
+        pipeline.channel_capacity.unwrap_or(self.channel_capacity)
+    }
+
+    fn check_alpaca(&self, problems: &mut Vec<String>) {
+        let missing = [
+            ("APCA_API_KEY_ID", self.alpaca.api_key.is_none()),
+            ("APCA_API_SECRET_KEY", self.alpaca.api_secret.is_none()),
+            ("APCA_WS_URL", self.alpaca.ws_url.is_none()),
+        ];
+        for (key, missing) in missing {
+            if missing {
+                problems.push(format!("{key} must be set"));
+            }
+        }
+    }
+}
+
+fn invalid(problems: Vec<String>) -> anyhow::Error {
+    anyhow!("invalid configuration:\n  - {}", problems.join("\n  - "))
 }
@@ -1,8 +1,10 @@
 //! Alpaca data connector primitives.
-//! Currently re-exporting the existing WebSocket client and message types.
+//! Re-exports the websocket client, replay source and message types.
 
+pub mod replay;
 pub mod types;
 pub mod websocket;
 
+pub use replay::{ReplaySource, ReplaySpeed};
 pub use types::{AlpacaMessage, Bar, Quote, Trade};
-pub use websocket::AlpacaWebSocketClient;
+pub use websocket::{AlpacaProtocol, AlpacaWebSocketClient};
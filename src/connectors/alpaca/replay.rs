@@ -0,0 +1,107 @@
+//! Replays recorded Alpaca websocket sessions as a message source.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+use crate::connectors::capture::CaptureReader;
+use crate::core::{MessageBatch, MessageSource};
+
+use super::types::AlpacaMessage;
+use super::websocket::parse_frame;
+
+/// Pacing applied when replaying a capture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Emit frames back to back without waiting.
+    AsFastAsPossible,
+    /// Reproduce the recorded gaps between frames, divided by `multiplier`.
+    Paced { multiplier: f64 },
+}
+
+impl ReplaySpeed {
+    /// Replays at the original recorded pace.
+    pub fn original() -> Self {
+        ReplaySpeed::Paced { multiplier: 1.0 }
+    }
+}
+
+/// `MessageSource` that feeds frames from a capture file through the Alpaca parser.
+pub struct ReplaySource {
+    path: PathBuf,
+    speed: ReplaySpeed,
+}
+
+impl ReplaySource {
+    /// Creates a replay source for the given capture file, emitting as fast as possible.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            speed: ReplaySpeed::AsFastAsPossible,
+        }
+    }
+
+    /// Overrides the replay pacing.
+    pub fn speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    async fn replay(
+        &self,
+        tx: tokio::sync::mpsc::Sender<MessageBatch<AlpacaMessage>>,
+    ) -> Result<()> {
+        if let ReplaySpeed::Paced { multiplier } = self.speed
+            && !(multiplier.is_finite() && multiplier > 0.0)
+        {
+            bail!("replay speed multiplier must be positive and finite, got {multiplier}");
+        }
+
+        let mut reader = CaptureReader::open(&self.path).await?;
+        if reader.header().source != "alpaca" {
+            bail!(
+                "capture {:?} was recorded from {}, not alpaca",
+                self.path,
+                reader.header().source
+            );
+        }
+        info!(path = ?self.path, speed = ?self.speed, "Replaying capture");
+
+        let mut previous: Option<DateTime<Utc>> = None;
+        let mut frames = 0usize;
+        while let Some(frame) = reader.next_frame().await? {
+            if let (ReplaySpeed::Paced { multiplier }, Some(previous)) = (self.speed, previous)
+                && let Ok(gap) = (frame.received_at - previous).to_std()
+            {
+                sleep(gap.div_f64(multiplier)).await;
+            }
+            previous = Some(frame.received_at);
+            frames += 1;
+
+            match parse_frame(&frame.frame) {
+                Ok(messages) => tx
+                    .send(messages)
+                    .await
+                    .context("Failed to send replayed messages")?,
+                Err(_) => debug!("Failed to parse replayed frame"),
+            }
+        }
+
+        info!(frames = frames, "Finished replaying capture");
+        Ok(())
+    }
+}
+
+impl MessageSource<AlpacaMessage> for ReplaySource {
+    fn run<'a>(
+        &'a mut self,
+        tx: tokio::sync::mpsc::Sender<MessageBatch<AlpacaMessage>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move { self.replay(tx).await })
+    }
+}
@@ -1,11 +1,11 @@
 //! Data types emitted by the Alpaca market data websocket.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::core::Message;
 
 /// All message variants that may be received from the Alpaca feed.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "T", rename_all = "lowercase")]
 pub enum AlpacaMessage {
     #[serde(rename = "success")]
@@ -51,8 +51,20 @@ pub enum AlpacaMessage {
 
 impl Message for AlpacaMessage {}
 
+impl AlpacaMessage {
+    /// Key under which quotes and bars of the same symbol replace each other when a
+    /// pipeline conflates; trades and control messages are never conflated.
+    pub fn conflation_key(&self) -> Option<String> {
+        match self {
+            AlpacaMessage::Quote(quote) => Some(format!("q:{}", quote.symbol)),
+            AlpacaMessage::Bar(bar) => Some(format!("b:{}", bar.symbol)),
+            _ => None,
+        }
+    }
+}
+
 /// OHLCV bar snapshot for a symbol.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Bar {
     #[serde(rename = "S")]
     pub symbol: String,
@@ -95,7 +107,7 @@ impl Bar {
 }
 
 /// Top-of-book quote update.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Quote {
     #[serde(rename = "S")]
     pub symbol: String,
@@ -141,9 +153,9 @@ impl Quote {
 }
 
 /// Executed trade tick.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Trade {
-    #[serde(rename = "T")]
+    #[serde(rename = "T", skip_serializing_if = "Option::is_none")]
     pub t: Option<String>,
 
     #[serde(rename = "S")]
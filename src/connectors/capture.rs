@@ -0,0 +1,148 @@
+//! Versioned on-disk format for raw websocket frames captured with receive timestamps.
+//!
+//! A capture is a JSON lines file whose first line is a [`CaptureHeader`] followed by
+//! one [`CapturedFrame`] per received text frame.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter, Lines};
+
+/// Identifier written into every capture header.
+pub const CAPTURE_FORMAT: &str = "tickflow-capture";
+
+/// Latest capture format version understood by this build.
+pub const CAPTURE_VERSION: u32 = 1;
+
+/// First line of a capture file describing its contents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureHeader {
+    pub format: String,
+    pub version: u32,
+    /// Connector that produced the frames, e.g. `alpaca`.
+    pub source: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Raw frame text together with the time it was received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    pub received_at: DateTime<Utc>,
+    pub frame: String,
+}
+
+/// Appends received frames to a capture file.
+pub struct CaptureRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CaptureRecorder {
+    /// Creates (or truncates) `path` and writes the capture header.
+    pub async fn create(path: impl AsRef<Path>, source: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)
+            .await
+            .with_context(|| format!("failed to create capture file {path:?}"))?;
+        let mut recorder = Self {
+            path,
+            writer: BufWriter::new(file),
+        };
+
+        let header = CaptureHeader {
+            format: CAPTURE_FORMAT.to_string(),
+            version: CAPTURE_VERSION,
+            source: source.to_string(),
+            started_at: Utc::now(),
+        };
+        recorder
+            .write_line(&serde_json::to_string(&header)?)
+            .await?;
+        Ok(recorder)
+    }
+
+    /// Records a frame stamped with the current time.
+    pub async fn record(&mut self, frame: &str) -> Result<()> {
+        self.record_at(Utc::now(), frame).await
+    }
+
+    /// Records a frame with an explicit receive timestamp.
+    pub async fn record_at(&mut self, received_at: DateTime<Utc>, frame: &str) -> Result<()> {
+        let entry = CapturedFrame {
+            received_at,
+            frame: frame.to_string(),
+        };
+        self.write_line(&serde_json::to_string(&entry)?).await
+    }
+
+    /// Path of the capture file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn write_line(&mut self, line: &str) -> Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+/// Sequential reader over the frames of a capture file.
+pub struct CaptureReader {
+    header: CaptureHeader,
+    lines: Lines<BufReader<File>>,
+}
+
+impl CaptureReader {
+    /// Opens a capture file and validates its header.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .await
+            .with_context(|| format!("failed to open capture file {path:?}"))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let first = lines
+            .next_line()
+            .await?
+            .with_context(|| format!("capture file {path:?} is empty"))?;
+        let header: CaptureHeader =
+            serde_json::from_str(&first).context("failed to parse capture header")?;
+
+        if header.format != CAPTURE_FORMAT {
+            bail!(
+                "{path:?} is not a tickflow capture (format {})",
+                header.format
+            );
+        }
+        if header.version > CAPTURE_VERSION {
+            bail!(
+                "unsupported capture version {} (max supported {CAPTURE_VERSION})",
+                header.version
+            );
+        }
+
+        Ok(Self { header, lines })
+    }
+
+    /// Header describing the capture.
+    pub fn header(&self) -> &CaptureHeader {
+        &self.header
+    }
+
+    /// Returns the next frame, or `None` at end of file.
+    pub async fn next_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        while let Some(line) = self.lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let frame = serde_json::from_str(&line).context("failed to parse captured frame")?;
+            return Ok(Some(frame));
+        }
+        Ok(None)
+    }
+}
@@ -3,6 +3,14 @@
 #[cfg(feature = "alpaca")]
 pub mod alpaca;
 
+#[cfg(feature = "websocket")]
+pub mod capture;
+
+pub mod rate_limit;
+
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
 #[cfg(feature = "yahoo")]
 pub mod yahoo;
 
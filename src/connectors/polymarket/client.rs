@@ -3,26 +3,35 @@
 use std::future::Future;
 use std::pin::Pin;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use polymarket_rs_client::ClobClient;
 use tokio::time::{Duration, sleep};
 use tracing::{debug, info, warn};
 
 use super::types::{Market, PolymarketMessage};
-use crate::core::{MessageBatch, MessageSource};
+use crate::core::{Checkpoint, MessageBatch, MessageSource, TrailingCheckpoint};
+use crate::secret::Secret;
 
 /// Polymarket API host
-const HOST: &str = "https://clob.polymarket.com";
+pub(crate) const HOST: &str = "https://clob.polymarket.com";
 
 /// Polygon chain ID
 const POLYGON: u64 = 137;
 
+/// Cursor value the CLOB API returns after the last page.
+pub(crate) const END_CURSOR: &str = "LTE=";
+
 /// Client for fetching Polymarket data.
+///
+/// Market listings are public, so [`PolymarketClient::public`] needs no credentials and
+/// fetching them never derives API keys, even when a private key is set.
 pub struct PolymarketClient {
-    /// Private key for authentication
-    private_key: String,
+    /// Private key for authentication; `None` in public mode
+    private_key: Option<Secret>,
     /// Delay between API requests in milliseconds
     request_delay_ms: u64,
+    /// Last page cursor of an interrupted bulk load
+    checkpoint: Option<Checkpoint>,
 }
 
 impl PolymarketClient {
@@ -31,20 +40,53 @@ impl PolymarketClient {
     /// # Arguments
     /// * `private_key` - Polymarket private key for API authentication
     /// * `request_delay_ms` - Delay between paginated API requests in milliseconds
-    pub fn new(private_key: String, request_delay_ms: u64) -> Self {
+    pub fn new(private_key: impl Into<Secret>, request_delay_ms: u64) -> Self {
         Self {
-            private_key,
+            private_key: Some(private_key.into()),
             request_delay_ms,
+            checkpoint: None,
         }
     }
 
-    /// Fetch all markets with pagination and send them through the channel.
-    async fn fetch_all_markets(
-        &self,
-        tx: tokio::sync::mpsc::Sender<MessageBatch<PolymarketMessage>>,
-    ) -> Result<()> {
-        // Initialize the CLOB client
-        let mut client = ClobClient::with_l1_headers(HOST, &self.private_key, POLYGON);
+    /// Create a read-only client that only uses public endpoints.
+    ///
+    /// # Arguments
+    /// * `request_delay_ms` - Delay between paginated API requests in milliseconds
+    pub fn public(request_delay_ms: u64) -> Self {
+        Self {
+            private_key: None,
+            request_delay_ms,
+            checkpoint: None,
+        }
+    }
+
+    /// Resumes the market listing from the last saved page cursor. Cursors are saved
+    /// once the pages before them must have reached the sink (see [`TrailingCheckpoint`]),
+    /// so a resumed run re-fetches up to a channel's worth of pages. The checkpoint is
+    /// cleared on completion.
+    pub fn checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Whether the client derives API credentials from a private key.
+    pub fn is_authenticated(&self) -> bool {
+        self.private_key.is_some()
+    }
+
+    /// CLOB client for public endpoints such as market listings; never derives keys.
+    fn public_client(&self) -> ClobClient {
+        ClobClient::new(HOST)
+    }
+
+    /// CLOB client with API credentials derived from the private key, for order and
+    /// account endpoints. Fails for clients built with [`PolymarketClient::public`].
+    pub async fn authenticated_client(&self) -> Result<ClobClient> {
+        let Some(private_key) = &self.private_key else {
+            bail!("Polymarket private key is required for authenticated endpoints");
+        };
+
+        let mut client = ClobClient::with_l1_headers(HOST, private_key.expose(), POLYGON);
 
         // Create or derive API keys
         let keys = client
@@ -52,39 +94,29 @@ impl PolymarketClient {
             .await
             .context("Failed to create or derive API key")?;
         client.set_api_creds(keys);
+        Ok(client)
+    }
 
-        let mut next_cursor: Option<String> = None;
-        let mut page_count = 0;
-        let mut total_markets = 0;
-
-        loop {
-            page_count += 1;
-            debug!(
-                page = page_count,
-                cursor = ?next_cursor,
-                "Fetching markets page"
-            );
-
-            let response = client
-                .get_markets(next_cursor.as_deref())
-                .await
-                .context("Failed to fetch markets")?;
-
-            // Extract data array from response
-            if let Some(data) = response.get("data").and_then(|d| d.as_array()) {
-                let page_markets_count = data.len();
-                info!(
-                    page = page_count,
-                    markets = page_markets_count,
-                    "Received markets page"
-                );
-
-                // Parse markets and send as messages
-                let messages: Vec<PolymarketMessage> = data
-                    .iter()
+    /// Fetch one page of markets, returning the parsed markets and the next cursor.
+    async fn fetch_page(
+        &self,
+        client: &ClobClient,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Market>, Option<String>)> {
+        let response = client
+            .get_markets(cursor)
+            .await
+            .context("Failed to fetch markets")?;
+
+        // Extract data array from response
+        let markets = response
+            .get("data")
+            .and_then(|d| d.as_array())
+            .map(|data| {
+                data.iter()
                     .filter_map(|market_json| {
                         match serde_json::from_value::<Market>(market_json.clone()) {
-                            Ok(market) => Some(PolymarketMessage::Market(market)),
+                            Ok(market) => Some(market),
                             Err(e) => {
                                 warn!(
                                     error = %e,
@@ -94,20 +126,103 @@ impl PolymarketClient {
                             }
                         }
                     })
-                    .collect();
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // An empty or terminal cursor marks the last page
+        let next_cursor = response
+            .get("next_cursor")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty() && *c != END_CURSOR)
+            .map(str::to_string);
+
+        Ok((markets, next_cursor))
+    }
 
-                if !messages.is_empty() {
-                    tx.send(messages)
-                        .await
-                        .context("Failed to send market messages")?;
-                }
+    /// Fetch every market, following pagination until the last page.
+    pub async fn fetch_markets(&self) -> Result<Vec<Market>> {
+        let client = self.public_client();
+        let mut markets = Vec::new();
+        let mut next_cursor: Option<String> = None;
+
+        loop {
+            let (page, cursor) = self.fetch_page(&client, next_cursor.as_deref()).await?;
+            markets.extend(page);
+
+            match cursor {
+                Some(cursor) => next_cursor = Some(cursor),
+                None => break,
+            }
+
+            // Rate limiting delay
+            sleep(Duration::from_millis(self.request_delay_ms)).await;
+        }
+
+        Ok(markets)
+    }
+
+    /// Fetch all markets with pagination and send them through the channel.
+    async fn fetch_all_markets(
+        &self,
+        tx: tokio::sync::mpsc::Sender<MessageBatch<PolymarketMessage>>,
+    ) -> Result<()> {
+        let client = self.public_client();
+        let mut trailing = self.checkpoint.clone().map(|checkpoint| {
+            TrailingCheckpoint::new(
+                checkpoint,
+                TrailingCheckpoint::in_flight(tx.max_capacity(), 1),
+            )
+        });
+
+        let mut next_cursor: Option<String> = match &self.checkpoint {
+            Some(checkpoint) => checkpoint.load().await?,
+            None => None,
+        };
+        if let Some(cursor) = &next_cursor {
+            info!(cursor = %cursor, "Resuming markets from checkpoint");
+        }
+        let mut page_count = 0;
+        let mut total_markets = 0;
+        let mut sent = 0;
 
-                total_markets += page_markets_count;
+        loop {
+            page_count += 1;
+            debug!(
+                page = page_count,
+                cursor = ?next_cursor,
+                "Fetching markets page"
+            );
+
+            let (markets, cursor) = self.fetch_page(&client, next_cursor.as_deref()).await?;
+            info!(
+                page = page_count,
+                markets = markets.len(),
+                "Received markets page"
+            );
+            total_markets += markets.len();
+
+            // Send parsed markets as messages
+            if !markets.is_empty() {
+                let messages = markets.into_iter().map(PolymarketMessage::Market).collect();
+                tx.send(messages)
+                    .await
+                    .context("Failed to send market messages")?;
+                sent += 1;
             }
 
             // Check if there's a next page
-            if let Some(cursor) = response.get("next_cursor").and_then(|c| c.as_str()) {
-                if cursor.is_empty() {
+            match cursor {
+                Some(cursor) => {
+                    if let Some(trailing) = &mut trailing {
+                        trailing.advance(&cursor, sent).await?;
+                    }
+                    next_cursor = Some(cursor);
+                }
+                None => {
+                    if let Some(trailing) = &mut trailing {
+                        trailing.clear().await?;
+                    }
                     info!(
                         total_markets = total_markets,
                         pages = page_count,
@@ -115,14 +230,6 @@ impl PolymarketClient {
                     );
                     break;
                 }
-                next_cursor = Some(cursor.to_string());
-            } else {
-                info!(
-                    total_markets = total_markets,
-                    pages = page_count,
-                    "Finished fetching all markets"
-                );
-                break;
             }
 
             // Rate limiting delay
@@ -1,7 +1,13 @@
 //! Polymarket prediction market data connector.
 
 pub mod client;
+pub mod poller;
+pub mod stream;
+pub mod sync;
 pub mod types;
 
 pub use client::PolymarketClient;
-pub use types::{Market, PolymarketMessage};
+pub use poller::{PolymarketPricePoller, Watchlist};
+pub use stream::{PolymarketMarketProtocol, PolymarketMarketStream};
+pub use sync::PolymarketMarketSync;
+pub use types::{Market, MarketEvent, MarketEventKind, PolymarketMessage, Rewards, Tag, Token};
@@ -0,0 +1,388 @@
+//! Polling source for Polymarket midpoints, spreads, last trades and price history.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{Duration, sleep};
+use tracing::{debug, info, warn};
+
+use super::client::{END_CURSOR, HOST};
+use super::types::{Market, OrderLevel, PolymarketMessage, PricePoint, PriceSnapshot};
+use crate::core::{MessageBatch, MessageSource};
+
+/// Selects which outcome tokens the poller tracks.
+///
+/// When `token_ids` is empty the market listing is scanned and filtered by the
+/// remaining fields instead.
+#[derive(Debug, Clone, Default)]
+pub struct Watchlist {
+    /// Explicit outcome token IDs; bypasses market filtering when non-empty
+    pub token_ids: Vec<String>,
+    /// Keep markets carrying at least one of these tags (all markets when empty)
+    pub tags: Vec<String>,
+    /// Keep only markets that are active and not closed
+    pub active_only: bool,
+    /// Minimum resting order book notional (price x size, both sides) per token
+    pub min_liquidity: Option<f64>,
+}
+
+impl Watchlist {
+    /// Watchlist of explicit token IDs.
+    pub fn tokens(token_ids: Vec<String>) -> Self {
+        Self {
+            token_ids,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `market` passes the tag and activity filters.
+    pub fn accepts(&self, market: &Market) -> bool {
+        if self.active_only && (!market.active || market.closed) {
+            return false;
+        }
+        if self.tags.is_empty() {
+            return true;
+        }
+        market.tags.iter().any(|tag| self.tags.contains(&tag.label))
+    }
+}
+
+/// Price history window requested on the first poll.
+#[derive(Debug, Clone)]
+struct HistoryOptions {
+    interval: String,
+    fidelity_minutes: u32,
+}
+
+#[derive(Deserialize)]
+struct MidpointResponse {
+    #[serde(default)]
+    mid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SpreadResponse {
+    #[serde(default)]
+    spread: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LastTradeResponse {
+    #[serde(default)]
+    price: Option<String>,
+    #[serde(default)]
+    side: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HistoryResponse {
+    #[serde(default)]
+    history: Vec<HistorySample>,
+}
+
+#[derive(Deserialize)]
+struct HistorySample {
+    t: i64,
+    p: f64,
+}
+
+#[derive(Deserialize)]
+struct BookResponse {
+    #[serde(default)]
+    bids: Vec<OrderLevel>,
+    #[serde(default)]
+    asks: Vec<OrderLevel>,
+}
+
+#[derive(Deserialize)]
+struct MarketsPage {
+    #[serde(default)]
+    data: Vec<serde_json::Value>,
+    #[serde(default)]
+    next_cursor: Option<String>,
+}
+
+/// Periodically polls public CLOB pricing endpoints for a watchlist of tokens.
+pub struct PolymarketPricePoller {
+    http: reqwest::Client,
+    host: String,
+    watchlist: Watchlist,
+    poll_interval: Duration,
+    /// Delay between API requests in milliseconds
+    request_delay_ms: u64,
+    history: Option<HistoryOptions>,
+    max_polls: Option<usize>,
+}
+
+impl PolymarketPricePoller {
+    /// Create a new poller.
+    ///
+    /// # Arguments
+    /// * `watchlist` - Tokens or market filters to poll
+    /// * `poll_interval` - Time between the start of consecutive polling rounds
+    /// * `request_delay_ms` - Delay between individual API requests in milliseconds
+    pub fn new(watchlist: Watchlist, poll_interval: Duration, request_delay_ms: u64) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            host: HOST.to_string(),
+            watchlist,
+            poll_interval,
+            request_delay_ms,
+            history: None,
+            max_polls: None,
+        }
+    }
+
+    /// Also fetches price history (`interval` such as `1d` or `max`, sampled every
+    /// `fidelity_minutes`) on the first round and new samples on later rounds.
+    pub fn with_history(mut self, interval: &str, fidelity_minutes: u32) -> Self {
+        self.history = Some(HistoryOptions {
+            interval: interval.to_string(),
+            fidelity_minutes,
+        });
+        self
+    }
+
+    /// Polls `host` instead of the public CLOB API.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Stops after the given number of polling rounds instead of running forever.
+    pub fn max_polls(mut self, polls: usize) -> Self {
+        self.max_polls = Some(polls);
+        self
+    }
+
+    /// Requests `path` and decodes the JSON body, then waits `request_delay_ms` whether
+    /// or not the request succeeded.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        let body = async {
+            self.http
+                .get(format!("{}{path}", self.host))
+                .query(query)
+                .send()
+                .await
+                .with_context(|| format!("Failed to request {path}"))?
+                .error_for_status()
+                .with_context(|| format!("Request to {path} failed"))?
+                .json::<T>()
+                .await
+                .with_context(|| format!("Failed to decode {path} response"))
+        }
+        .await;
+
+        // Rate limiting delay
+        sleep(Duration::from_millis(self.request_delay_ms)).await;
+        body
+    }
+
+    /// Resolves the watchlist into concrete token IDs.
+    async fn resolve_tokens(&self) -> Result<Vec<String>> {
+        if !self.watchlist.token_ids.is_empty() {
+            return Ok(self.watchlist.token_ids.clone());
+        }
+
+        let mut token_ids = Vec::new();
+        let mut next_cursor: Option<String> = None;
+        loop {
+            let mut query = Vec::new();
+            if let Some(cursor) = &next_cursor {
+                query.push(("next_cursor", cursor.clone()));
+            }
+            let page: MarketsPage = self.get_json("/markets", &query).await?;
+
+            for market_json in page.data {
+                match serde_json::from_value::<Market>(market_json) {
+                    Ok(market) if self.watchlist.accepts(&market) => {
+                        token_ids.extend(market_token_ids(&market));
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!(error = %e, "Failed to parse market, skipping"),
+                }
+            }
+
+            match page.next_cursor {
+                Some(cursor) if !cursor.is_empty() && cursor != END_CURSOR => {
+                    next_cursor = Some(cursor)
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(min_liquidity) = self.watchlist.min_liquidity {
+            let mut liquid = Vec::with_capacity(token_ids.len());
+            for token_id in token_ids {
+                match self.book_liquidity(&token_id).await {
+                    Ok(liquidity) if liquidity >= min_liquidity => liquid.push(token_id),
+                    Ok(_) => {}
+                    Err(e) => debug!(token_id = %token_id, error = %e, "No order book, skipping"),
+                }
+            }
+            token_ids = liquid;
+        }
+
+        info!(tokens = token_ids.len(), "Resolved polymarket watchlist");
+        Ok(token_ids)
+    }
+
+    async fn book_liquidity(&self, token_id: &str) -> Result<f64> {
+        let book: BookResponse = self
+            .get_json("/book", &[("token_id", token_id.to_string())])
+            .await?;
+        Ok(book
+            .bids
+            .iter()
+            .chain(book.asks.iter())
+            .map(|level| level.price * level.size)
+            .sum())
+    }
+
+    async fn snapshot(&self, token_id: &str) -> PriceSnapshot {
+        let query = [("token_id", token_id.to_string())];
+
+        let midpoint = match self.get_json::<MidpointResponse>("/midpoint", &query).await {
+            Ok(response) => parse_price(response.mid),
+            Err(e) => {
+                debug!(token_id = %token_id, error = %e, "Failed to fetch midpoint");
+                None
+            }
+        };
+        let spread = match self.get_json::<SpreadResponse>("/spread", &query).await {
+            Ok(response) => parse_price(response.spread),
+            Err(e) => {
+                debug!(token_id = %token_id, error = %e, "Failed to fetch spread");
+                None
+            }
+        };
+        let (last_trade_price, last_trade_side) = match self
+            .get_json::<LastTradeResponse>("/last-trade-price", &query)
+            .await
+        {
+            Ok(response) => (parse_price(response.price), response.side),
+            Err(e) => {
+                debug!(token_id = %token_id, error = %e, "Failed to fetch last trade price");
+                (None, None)
+            }
+        };
+
+        PriceSnapshot {
+            token_id: token_id.to_string(),
+            midpoint,
+            spread,
+            last_trade_price,
+            last_trade_side,
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    async fn price_history(
+        &self,
+        token_id: &str,
+        history: &HistoryOptions,
+        since: Option<i64>,
+    ) -> Result<Vec<PricePoint>> {
+        let mut query = vec![
+            ("market", token_id.to_string()),
+            ("fidelity", history.fidelity_minutes.to_string()),
+        ];
+        match since {
+            Some(start) => query.push(("startTs", (start + 1).to_string())),
+            None => query.push(("interval", history.interval.clone())),
+        }
+
+        let response: HistoryResponse = self.get_json("/prices-history", &query).await?;
+        Ok(response
+            .history
+            .into_iter()
+            .filter(|sample| since.is_none_or(|start| sample.t > start))
+            .map(|sample| PricePoint {
+                token_id: token_id.to_string(),
+                timestamp: sample.t,
+                price: sample.p,
+            })
+            .collect())
+    }
+
+    async fn poll(&self, tx: Sender<MessageBatch<PolymarketMessage>>) -> Result<()> {
+        let token_ids = self.resolve_tokens().await?;
+        let mut history_cursor: HashMap<String, i64> = HashMap::new();
+        let mut round = 0usize;
+
+        loop {
+            round += 1;
+            let started = tokio::time::Instant::now();
+            debug!(
+                round = round,
+                tokens = token_ids.len(),
+                "Polling polymarket prices"
+            );
+
+            for token_id in &token_ids {
+                let mut messages = vec![PolymarketMessage::PriceSnapshot(
+                    self.snapshot(token_id).await,
+                )];
+
+                if let Some(history) = &self.history {
+                    let since = history_cursor.get(token_id).copied();
+                    match self.price_history(token_id, history, since).await {
+                        Ok(points) => {
+                            if let Some(last) = points.iter().map(|p| p.timestamp).max() {
+                                history_cursor.insert(token_id.clone(), last);
+                            }
+                            messages.extend(points.into_iter().map(PolymarketMessage::PricePoint));
+                        }
+                        Err(e) => {
+                            warn!(token_id = %token_id, error = %e, "Failed to fetch price history")
+                        }
+                    }
+                }
+
+                tx.send(messages)
+                    .await
+                    .context("Failed to send price messages")?;
+            }
+
+            if self.max_polls.is_some_and(|max| round >= max) {
+                info!(rounds = round, "Finished polling polymarket prices");
+                return Ok(());
+            }
+            if let Some(remaining) = self.poll_interval.checked_sub(started.elapsed()) {
+                sleep(remaining).await;
+            }
+        }
+    }
+}
+
+impl MessageSource<PolymarketMessage> for PolymarketPricePoller {
+    fn run<'a>(
+        &'a mut self,
+        tx: Sender<MessageBatch<PolymarketMessage>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move { self.poll(tx).await })
+    }
+}
+
+fn parse_price(value: Option<String>) -> Option<f64> {
+    value.and_then(|v| v.trim().parse().ok())
+}
+
+fn market_token_ids(market: &Market) -> Vec<String> {
+    market
+        .tokens
+        .iter()
+        .filter(|token| !token.token_id.is_empty())
+        .map(|token| token.token_id.clone())
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Polymarket CLOB market channel streaming order book and trade events.
+
+use anyhow::Result;
+use serde_json::json;
+use tokio::time::Duration;
+
+use super::types::{MarketChannelEvent, PolymarketMessage};
+use crate::connectors::websocket::{Control, Protocol, WebSocketSource};
+
+/// Public market channel endpoint of the CLOB websocket.
+pub const MARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+
+/// Interval at which the market channel expects a `PING` keepalive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Streams book snapshots, price changes, tick size changes and trades for a set of tokens.
+pub type PolymarketMarketStream = WebSocketSource<PolymarketMarketProtocol>;
+
+/// Subscription and parsing rules for the unauthenticated market channel.
+pub struct PolymarketMarketProtocol {
+    url: String,
+    token_ids: Vec<String>,
+}
+
+impl PolymarketMarketProtocol {
+    /// Creates the protocol for the given websocket URL and outcome token IDs.
+    pub fn new(url: &str, token_ids: Vec<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            token_ids,
+        }
+    }
+}
+
+impl WebSocketSource<PolymarketMarketProtocol> {
+    /// Creates a market channel stream for the given outcome token IDs.
+    pub fn new(token_ids: Vec<String>) -> Self {
+        Self::with_protocol(PolymarketMarketProtocol::new(MARKET_WS_URL, token_ids))
+    }
+}
+
+impl Protocol for PolymarketMarketProtocol {
+    type Message = PolymarketMessage;
+
+    fn name(&self) -> &'static str {
+        "polymarket"
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn auth_payload(&self) -> Option<String> {
+        None
+    }
+
+    fn subscribe_payload(&self) -> Option<String> {
+        let payload = json!({
+            "assets_ids": self.token_ids,
+            "type": "market"
+        });
+        Some(payload.to_string())
+    }
+
+    fn parse(&self, frame: &str) -> Result<Vec<PolymarketMessage>> {
+        let frame = frame.trim();
+        if frame == "PONG" {
+            return Ok(Vec::new());
+        }
+
+        let events = if frame.starts_with('[') {
+            serde_json::from_str::<Vec<MarketChannelEvent>>(frame)?
+        } else {
+            vec![serde_json::from_str::<MarketChannelEvent>(frame)?]
+        };
+        Ok(events
+            .into_iter()
+            .filter_map(MarketChannelEvent::into_message)
+            .collect())
+    }
+
+    fn classify(&self, _message: &PolymarketMessage) -> Control {
+        Control::Data
+    }
+
+    fn heartbeat(&self) -> Option<(Duration, String)> {
+        Some((HEARTBEAT_INTERVAL, "PING".to_string()))
+    }
+}
@@ -0,0 +1,265 @@
+//! Incremental Polymarket market sync that reports lifecycle changes.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{Duration, Instant, sleep};
+use tracing::{debug, info, warn};
+
+use super::client::PolymarketClient;
+use super::types::{FieldChange, Market, MarketEvent, MarketEventKind, PolymarketMessage};
+use crate::core::{MessageBatch, MessageSource};
+
+/// Number of messages sent per batch.
+const BATCH_SIZE: usize = 500;
+
+/// Loads the stored markets a sync round diffs against.
+pub type BaselineLoader =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = Result<Vec<Market>>> + Send>> + Send + Sync>;
+
+/// Periodically re-fetches all markets and diffs them against the last known state.
+///
+/// Each round emits a `Market` message for every new or changed market followed by
+/// its [`MarketEvent`]. Unchanged markets are not re-sent. A round whose markets cannot
+/// be fetched is logged and skipped.
+pub struct PolymarketMarketSync {
+    client: PolymarketClient,
+    interval: Duration,
+    known: HashMap<String, Market>,
+    reload: Option<BaselineLoader>,
+    max_syncs: Option<usize>,
+}
+
+impl PolymarketMarketSync {
+    /// Create a new sync.
+    ///
+    /// # Arguments
+    /// * `client` - Client used to list markets
+    /// * `interval` - Time between the start of consecutive syncs
+    pub fn new(client: PolymarketClient, interval: Duration) -> Self {
+        Self {
+            client,
+            interval,
+            known: HashMap::new(),
+            reload: None,
+            max_syncs: None,
+        }
+    }
+
+    /// Seeds the last known state, typically loaded from storage, so restarts do not
+    /// report every stored market as opened.
+    pub fn baseline(mut self, markets: Vec<Market>) -> Self {
+        self.known = markets
+            .into_iter()
+            .map(|market| (market.condition_id.clone(), market))
+            .collect();
+        self
+    }
+
+    /// Reloads the last known state before every round, so a change whose write failed
+    /// is detected again on the next round instead of being lost.
+    pub fn reload_baseline(mut self, load: BaselineLoader) -> Self {
+        self.reload = Some(load);
+        self
+    }
+
+    /// Stops after the given number of syncs instead of running forever.
+    pub fn max_syncs(mut self, syncs: usize) -> Self {
+        self.max_syncs = Some(syncs);
+        self
+    }
+
+    async fn sync(&mut self, tx: Sender<MessageBatch<PolymarketMessage>>) -> Result<()> {
+        let mut round = 0usize;
+
+        loop {
+            round += 1;
+            let started = Instant::now();
+            if let Err(err) = self.round(round, &tx).await {
+                if tx.is_closed() {
+                    return Err(err);
+                }
+                warn!(round = round, "Polymarket market sync failed: {err:#}");
+            }
+
+            if self.max_syncs.is_some_and(|max| round >= max) {
+                return Ok(());
+            }
+            if let Some(remaining) = self.interval.checked_sub(started.elapsed()) {
+                debug!(wait = ?remaining, "Waiting for next market sync");
+                sleep(remaining).await;
+            }
+        }
+    }
+
+    /// Fetches all markets once and sends the changes; the known state only advances
+    /// once they were sent.
+    async fn round(
+        &mut self,
+        round: usize,
+        tx: &Sender<MessageBatch<PolymarketMessage>>,
+    ) -> Result<()> {
+        if let Some(load) = &self.reload {
+            let markets = load().await.context("Failed to load stored markets")?;
+            self.known = markets
+                .into_iter()
+                .map(|market| (market.condition_id.clone(), market))
+                .collect();
+        }
+        let markets = self.client.fetch_markets().await?;
+        let detected_at = Utc::now().timestamp_millis();
+
+        let mut messages = Vec::new();
+        let mut changed = Vec::new();
+        let mut counts: HashMap<MarketEventKind, usize> = HashMap::new();
+        for market in markets {
+            let previous = self.known.get(&market.condition_id);
+            if let Some(event) = diff_market(previous, &market, detected_at) {
+                *counts.entry(event.kind).or_default() += 1;
+                messages.push(PolymarketMessage::Market(market.clone()));
+                messages.push(PolymarketMessage::MarketEvent(event));
+                changed.push(market);
+            }
+        }
+
+        info!(
+            round = round,
+            opened = counts.get(&MarketEventKind::Opened).copied().unwrap_or(0),
+            closed = counts.get(&MarketEventKind::Closed).copied().unwrap_or(0),
+            resolved = counts.get(&MarketEventKind::Resolved).copied().unwrap_or(0),
+            updated = counts.get(&MarketEventKind::Updated).copied().unwrap_or(0),
+            "Finished polymarket market sync"
+        );
+
+        while !messages.is_empty() {
+            let rest = messages.split_off(messages.len().min(BATCH_SIZE));
+            tx.send(messages)
+                .await
+                .context("Failed to send market sync messages")?;
+            messages = rest;
+        }
+
+        for market in changed {
+            self.known.insert(market.condition_id.clone(), market);
+        }
+        Ok(())
+    }
+}
+
+impl MessageSource<PolymarketMessage> for PolymarketMarketSync {
+    fn run<'a>(
+        &'a mut self,
+        tx: Sender<MessageBatch<PolymarketMessage>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move { self.sync(tx).await })
+    }
+}
+
+/// Compares a market with its previous state and describes the change, if any.
+///
+/// A market that both closes and declares a winner is reported once as `Resolved`.
+pub fn diff_market(
+    previous: Option<&Market>,
+    current: &Market,
+    detected_at: i64,
+) -> Option<MarketEvent> {
+    let current_fields = tracked_fields(current);
+
+    let Some(previous) = previous else {
+        let changes = current_fields
+            .into_iter()
+            .map(|(field, value)| FieldChange {
+                field: field.to_string(),
+                old_value: None,
+                new_value: value,
+            })
+            .collect();
+        return Some(MarketEvent {
+            condition_id: current.condition_id.clone(),
+            kind: MarketEventKind::Opened,
+            changes,
+            detected_at,
+        });
+    };
+
+    let changes: Vec<FieldChange> = tracked_fields(previous)
+        .into_iter()
+        .zip(current_fields)
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field: field.to_string(),
+            old_value: old,
+            new_value: new,
+        })
+        .collect();
+    if changes.is_empty() {
+        return None;
+    }
+
+    let had_winner = previous.tokens.iter().any(|token| token.winner);
+    let has_winner = current.tokens.iter().any(|token| token.winner);
+    let kind = if has_winner && !had_winner {
+        MarketEventKind::Resolved
+    } else if current.closed && !previous.closed {
+        MarketEventKind::Closed
+    } else {
+        MarketEventKind::Updated
+    };
+
+    Some(MarketEvent {
+        condition_id: current.condition_id.clone(),
+        kind,
+        changes,
+        detected_at,
+    })
+}
+
+/// Fields whose changes are tracked; volatile data such as token prices is excluded.
+fn tracked_fields(market: &Market) -> Vec<(&'static str, Option<String>)> {
+    let winners: Vec<&str> = market
+        .tokens
+        .iter()
+        .filter(|token| token.winner)
+        .map(|token| token.outcome.as_str())
+        .collect();
+    let tags: Vec<&str> = market.tags.iter().map(|tag| tag.label.as_str()).collect();
+
+    vec![
+        ("question", market.question.clone()),
+        ("description", market.description.clone()),
+        ("active", Some(market.active.to_string())),
+        ("closed", Some(market.closed.to_string())),
+        ("archived", Some(market.archived.to_string())),
+        (
+            "accepting_orders",
+            Some(market.accepting_orders.to_string()),
+        ),
+        (
+            "enable_order_book",
+            Some(market.enable_order_book.to_string()),
+        ),
+        ("end_date_iso", market.end_date_iso.clone()),
+        ("game_start_time", market.game_start_time.clone()),
+        (
+            "minimum_order_size",
+            Some(market.minimum_order_size.to_string()),
+        ),
+        (
+            "minimum_tick_size",
+            Some(market.minimum_tick_size.to_string()),
+        ),
+        ("maker_base_fee", Some(market.maker_base_fee.to_string())),
+        ("taker_base_fee", Some(market.taker_base_fee.to_string())),
+        ("seconds_delay", Some(market.seconds_delay.to_string())),
+        ("rewards", serde_json::to_string(&market.rewards).ok()),
+        ("tags", Some(tags.join(","))),
+        (
+            "winning_outcome",
+            (!winners.is_empty()).then(|| winners.join(",")),
+        ),
+    ]
+}
@@ -1,8 +1,8 @@
 //! Polymarket data types for market information.
 
 use crate::core::Message;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
 /// Represents a Polymarket prediction market.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,17 +82,17 @@ pub struct Market {
     #[serde(default)]
     pub seconds_delay: i32,
 
-    /// Token information (stored as JSONB)
-    #[serde(default)]
-    pub tokens: Value,
+    /// Outcome tokens of the market
+    #[serde(default, deserialize_with = "de_null_default")]
+    pub tokens: Vec<Token>,
 
-    /// Reward configuration (stored as JSONB)
-    #[serde(default)]
-    pub rewards: Value,
+    /// Liquidity reward configuration
+    #[serde(default, deserialize_with = "de_null_default")]
+    pub rewards: Rewards,
 
-    /// Tags associated with the market (stored as JSONB)
-    #[serde(default)]
-    pub tags: Value,
+    /// Tags associated with the market
+    #[serde(default, deserialize_with = "de_null_default")]
+    pub tags: Vec<Tag>,
 
     /// Icon URL
     #[serde(default)]
@@ -123,11 +123,369 @@ pub struct Market {
     pub is_50_50_outcome: bool,
 }
 
+/// An outcome token of a market, such as `Yes` or `No`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Token {
+    /// ERC1155 token identifier used by the order book
+    #[serde(default)]
+    pub token_id: String,
+
+    /// Outcome label
+    #[serde(default)]
+    pub outcome: String,
+
+    /// Last known price of the outcome
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub price: Option<f64>,
+
+    /// Whether this outcome won once the market resolved
+    #[serde(default)]
+    pub winner: bool,
+
+    /// Fields not modelled above, kept for forward compatibility
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Liquidity reward configuration of a market.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rewards {
+    /// Daily reward rates per reward asset
+    #[serde(default, deserialize_with = "de_null_default")]
+    pub rates: Vec<RewardRate>,
+
+    /// Minimum order size eligible for rewards
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub min_size: Option<f64>,
+
+    /// Maximum spread from the midpoint eligible for rewards, in cents
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub max_spread: Option<f64>,
+
+    /// Fields not modelled above, kept for forward compatibility
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Daily reward rate paid in a given asset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RewardRate {
+    #[serde(default)]
+    pub asset_address: String,
+
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub rewards_daily_rate: Option<f64>,
+
+    /// Fields not modelled above, kept for forward compatibility
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Market tag; the CLOB API sends plain labels while richer APIs send objects.
+///
+/// Tags serialize back in the shape they arrived in: a label without a slug or extra
+/// fields as a plain string, anything else as an object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TagRepr", into = "TagRepr")]
+pub struct Tag {
+    pub label: String,
+
+    pub slug: Option<String>,
+
+    /// Fields not modelled above, kept for forward compatibility
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TagRepr {
+    Label(String),
+    Object {
+        #[serde(default)]
+        label: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        slug: Option<String>,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
+}
+
+impl From<TagRepr> for Tag {
+    fn from(repr: TagRepr) -> Self {
+        match repr {
+            TagRepr::Label(label) => Tag {
+                label,
+                ..Tag::default()
+            },
+            TagRepr::Object { label, slug, extra } => Tag { label, slug, extra },
+        }
+    }
+}
+
+impl From<Tag> for TagRepr {
+    fn from(tag: Tag) -> Self {
+        let Tag { label, slug, extra } = tag;
+        if slug.is_none() && extra.is_empty() {
+            TagRepr::Label(label)
+        } else {
+            TagRepr::Object { label, slug, extra }
+        }
+    }
+}
+
+/// A single price level of an order book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderLevel {
+    #[serde(deserialize_with = "de_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub size: f64,
+}
+
+/// Full order book snapshot for one outcome token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    /// Outcome token identifier
+    pub asset_id: String,
+    /// Condition identifier of the market
+    pub market: String,
+    #[serde(default, alias = "buys")]
+    pub bids: Vec<OrderLevel>,
+    #[serde(default, alias = "sells")]
+    pub asks: Vec<OrderLevel>,
+    /// Exchange timestamp in milliseconds since the epoch
+    #[serde(deserialize_with = "de_i64")]
+    pub timestamp: i64,
+    /// Hash of the book state
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+/// Change to one price level of a token's book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceLevelChange {
+    pub asset_id: String,
+    #[serde(deserialize_with = "de_f64")]
+    pub price: f64,
+    /// New aggregate size at the level (0 removes it)
+    #[serde(deserialize_with = "de_f64")]
+    pub size: f64,
+    /// `BUY` or `SELL`
+    pub side: String,
+    #[serde(default)]
+    pub hash: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub best_bid: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub best_ask: Option<f64>,
+}
+
+/// Batch of price level changes emitted for a market.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceChange {
+    /// Condition identifier of the market
+    pub market: String,
+    pub price_changes: Vec<PriceLevelChange>,
+    /// Exchange timestamp in milliseconds since the epoch
+    #[serde(deserialize_with = "de_i64")]
+    pub timestamp: i64,
+}
+
+/// Minimum tick size update for a token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickSizeChange {
+    pub asset_id: String,
+    pub market: String,
+    #[serde(deserialize_with = "de_f64")]
+    pub old_tick_size: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub new_tick_size: f64,
+    /// Exchange timestamp in milliseconds since the epoch
+    #[serde(deserialize_with = "de_i64")]
+    pub timestamp: i64,
+}
+
+/// Trade executed on a token's book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastTrade {
+    pub asset_id: String,
+    pub market: String,
+    #[serde(deserialize_with = "de_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub size: f64,
+    /// `BUY` or `SELL`
+    pub side: String,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub fee_rate_bps: Option<f64>,
+    /// Exchange timestamp in milliseconds since the epoch
+    #[serde(deserialize_with = "de_i64")]
+    pub timestamp: i64,
+}
+
+/// Polled pricing state of a token at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSnapshot {
+    pub token_id: String,
+    /// Midpoint between best bid and best ask
+    pub midpoint: Option<f64>,
+    /// Difference between best ask and best bid
+    pub spread: Option<f64>,
+    pub last_trade_price: Option<f64>,
+    /// `BUY` or `SELL` side of the last trade
+    pub last_trade_side: Option<String>,
+    /// Poll time in milliseconds since the epoch
+    pub timestamp: i64,
+}
+
+/// Historical price sample for a token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub token_id: String,
+    /// Sample time in seconds since the epoch
+    pub timestamp: i64,
+    pub price: f64,
+}
+
+/// Lifecycle transition detected between two syncs of a market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketEventKind {
+    /// First time the market was seen
+    Opened,
+    /// The market stopped trading
+    Closed,
+    /// A winning outcome was declared; usually closes the market too
+    Resolved,
+    /// Any other tracked field changed
+    Updated,
+}
+
+impl MarketEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketEventKind::Opened => "opened",
+            MarketEventKind::Closed => "closed",
+            MarketEventKind::Resolved => "resolved",
+            MarketEventKind::Updated => "updated",
+        }
+    }
+}
+
+/// Old and new value of a tracked market field, rendered as text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// `None` when the market was first seen
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Change of a market between two syncs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketEvent {
+    pub condition_id: String,
+    pub kind: MarketEventKind,
+    /// Every tracked field that changed, or all tracked fields for `Opened`
+    pub changes: Vec<FieldChange>,
+    /// Detection time in milliseconds since the epoch
+    pub detected_at: i64,
+}
+
+/// Event received on the CLOB market websocket channel.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum MarketChannelEvent {
+    Book(BookSnapshot),
+    PriceChange(PriceChange),
+    TickSizeChange(TickSizeChange),
+    LastTradePrice(LastTrade),
+    #[serde(other)]
+    Unknown,
+}
+
+impl MarketChannelEvent {
+    /// Converts the wire event into a pipeline message, dropping unknown event types.
+    pub fn into_message(self) -> Option<PolymarketMessage> {
+        match self {
+            MarketChannelEvent::Book(book) => Some(PolymarketMessage::Book(book)),
+            MarketChannelEvent::PriceChange(change) => Some(PolymarketMessage::PriceChange(change)),
+            MarketChannelEvent::TickSizeChange(change) => {
+                Some(PolymarketMessage::TickSizeChange(change))
+            }
+            MarketChannelEvent::LastTradePrice(trade) => Some(PolymarketMessage::LastTrade(trade)),
+            MarketChannelEvent::Unknown => None,
+        }
+    }
+}
+
 /// Message types from Polymarket data source.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PolymarketMessage {
     /// A market listing
     Market(Market),
+    /// Order book snapshot from the market channel
+    Book(BookSnapshot),
+    /// Order book level changes from the market channel
+    PriceChange(PriceChange),
+    /// Tick size change from the market channel
+    TickSizeChange(TickSizeChange),
+    /// Last trade price from the market channel
+    LastTrade(LastTrade),
+    /// Polled midpoint, spread and last trade price
+    PriceSnapshot(PriceSnapshot),
+    /// Sample from the price history endpoint
+    PricePoint(PricePoint),
+    /// Change detected by the incremental market sync
+    MarketEvent(MarketEvent),
 }
 
 impl Message for PolymarketMessage {}
+
+/// Polymarket encodes most numbers as strings; accept either representation.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Float(f64),
+    Int(i64),
+}
+
+fn de_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+        StringOrNumber::Float(f) => Ok(f),
+        StringOrNumber::Int(i) => Ok(i as f64),
+    }
+}
+
+fn de_opt_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    match Option::<StringOrNumber>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(StringOrNumber::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(StringOrNumber::String(s)) => {
+            s.trim().parse().map(Some).map_err(serde::de::Error::custom)
+        }
+        Some(StringOrNumber::Float(f)) => Ok(Some(f)),
+        Some(StringOrNumber::Int(i)) => Ok(Some(i as f64)),
+    }
+}
+
+fn de_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+        StringOrNumber::Float(f) => Ok(f as i64),
+        StringOrNumber::Int(i) => Ok(i),
+    }
+}
+
+/// Treats an explicit `null` like a missing field.
+fn de_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
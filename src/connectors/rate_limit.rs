@@ -0,0 +1,128 @@
+//! Token-bucket rate limiter with adaptive slowdown for HTTP connectors.
+
+use std::future::Future;
+use std::sync::Mutex;
+
+use tokio::time::{Duration, Instant, sleep};
+use tracing::warn;
+
+/// HTTP status code of Too Many Requests responses.
+const TOO_MANY_REQUESTS: u16 = 429;
+
+/// Share of the configured rate restored after each successful request.
+const RECOVERY_STEP: f64 = 0.05;
+
+/// Lowest rate the limiter slows down to, as a share of the configured rate.
+const MIN_RATE_FACTOR: f64 = 1.0 / 16.0;
+
+/// Longest single wait for a token; slower rates wait in several steps.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+/// Limits requests to a steady rate with short bursts.
+///
+/// Halves its rate whenever the remote side reports rate limiting (HTTP 429) and
+/// gradually recovers to the configured rate as requests succeed again.
+pub struct RateLimiter {
+    max_rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests_per_second` on average and up to `burst`
+    /// requests at once.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let max_rate = requests_per_second.max(f64::MIN_POSITIVE);
+        let burst = f64::from(burst.max(1));
+        Self {
+            max_rate,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                rate: max_rate,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Limiter allowing one request per `interval`, without bursts.
+    pub fn per_interval(interval: Duration) -> Self {
+        Self::new(1.0 / interval.as_secs_f64().max(1e-3), 1)
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                self.refill(&mut bucket);
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::try_from_secs_f64((1.0 - bucket.tokens) / bucket.rate)
+                    .map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT))
+            };
+            sleep(wait).await;
+        }
+    }
+
+    /// Waits for a slot, runs the request and adapts the rate to its outcome.
+    pub async fn run<T, E: HttpStatus>(
+        &self,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        self.acquire().await;
+        let result = request.await;
+        match &result {
+            Ok(_) => self.on_success(),
+            Err(e) if is_rate_limited(e) => self.on_rate_limited(),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Records a successful request, recovering towards the configured rate.
+    pub fn on_success(&self) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.rate = (bucket.rate + self.max_rate * RECOVERY_STEP).min(self.max_rate);
+    }
+
+    /// Records a rate-limited request: halves the rate and drains queued tokens.
+    pub fn on_rate_limited(&self) {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        bucket.rate = (bucket.rate / 2.0).max(self.max_rate * MIN_RATE_FACTOR);
+        bucket.tokens = bucket.tokens.min(0.0);
+        warn!(rate = bucket.rate, "Rate limited, slowing down");
+    }
+
+    /// Current allowed requests per second.
+    pub fn rate(&self) -> f64 {
+        self.bucket.lock().unwrap().rate
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(self.burst);
+        bucket.last_refill = now;
+    }
+}
+
+/// Request errors that can report the HTTP status of the failed response.
+pub trait HttpStatus {
+    /// Status code of the response, or `None` when the request failed before one arrived.
+    fn http_status(&self) -> Option<u16>;
+}
+
+/// Whether a request failed with HTTP 429 Too Many Requests.
+pub fn is_rate_limited(error: &impl HttpStatus) -> bool {
+    error.http_status() == Some(TOO_MANY_REQUESTS)
+}
@@ -0,0 +1,397 @@
+//! Reusable websocket source driven by a vendor-specific [`Protocol`].
+//!
+//! The shared loop owns connection setup, reconnect with backoff, ping/pong and frame
+//! capture, while the protocol supplies payloads, parsing and control classification.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Result, bail};
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc::Sender;
+use tokio::time::{Duration, Instant, Interval, interval_at, sleep};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::protocol::Message as WsMessage;
+use tracing::{Instrument, debug, error, info, info_span, trace, warn};
+
+use crate::connectors::capture::CaptureRecorder;
+use crate::core::{Message, MessageBatch, MessageSource};
+use crate::pipeline::PipelineStatus;
+use crate::secret::redact_credentials;
+
+/// Raw frames logged at debug level: one in every this many. Every frame goes to trace.
+const DEFAULT_FRAME_SAMPLE: u64 = 1_000;
+
+/// Sessions lasting at least this long count as stable even if they delivered no data.
+const STABLE_SESSION: Duration = Duration::from_secs(60);
+
+/// Characters of a frame kept when it is logged.
+const MAX_LOGGED_FRAME: usize = 512;
+
+/// Distinguishes the sessions of all websocket sources in the logs.
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How the shared loop should react to a parsed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Control {
+    /// Regular market data.
+    Data,
+    /// Informational control message such as an auth or subscription acknowledgement.
+    Status(String),
+    /// Recoverable error reported by the vendor.
+    Error(String),
+    /// Unrecoverable error; the source stops without reconnecting.
+    Fatal(String),
+}
+
+/// Vendor-specific half of a websocket source.
+pub trait Protocol: Send + Sync + 'static {
+    type Message: Message;
+
+    /// Short connector name used in logs and capture headers.
+    fn name(&self) -> &'static str;
+
+    /// Websocket endpoint to connect to.
+    fn url(&self) -> &str;
+
+    /// Payload sent right after connecting, if the vendor requires authentication.
+    fn auth_payload(&self) -> Option<String>;
+
+    /// Payload sent after authentication to select channels.
+    fn subscribe_payload(&self) -> Option<String>;
+
+    /// Parses a text frame into zero or more messages.
+    fn parse(&self, frame: &str) -> Result<Vec<Self::Message>>;
+
+    /// Classifies a parsed message so the shared loop can log or stop on it.
+    fn classify(&self, message: &Self::Message) -> Control;
+
+    /// Application-level keepalive text and the period it must be sent at.
+    fn heartbeat(&self) -> Option<(Duration, String)> {
+        None
+    }
+}
+
+/// Backoff settings applied when the connection drops or cannot be established.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Consecutive reconnect attempts allowed; `None` retries forever. Only a session
+    /// that delivered data or stayed up for a minute resets the count.
+    pub max_retries: Option<u32>,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Never reconnects; the source finishes when the first connection ends.
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: Some(0),
+            ..Self::default()
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Why a single websocket session ended.
+enum SessionEnd {
+    Disconnected,
+    ChannelClosed,
+    Fatal(String),
+}
+
+/// `MessageSource` that streams a websocket described by a [`Protocol`].
+pub struct WebSocketSource<P: Protocol> {
+    protocol: P,
+    reconnect: ReconnectPolicy,
+    ping_interval: Option<Duration>,
+    record_path: Option<PathBuf>,
+    frame_sample: u64,
+    status: Option<PipelineStatus>,
+}
+
+impl<P: Protocol> WebSocketSource<P> {
+    /// Creates a source for the given protocol with default reconnect settings.
+    pub fn with_protocol(protocol: P) -> Self {
+        Self {
+            protocol,
+            reconnect: ReconnectPolicy::default(),
+            ping_interval: None,
+            record_path: None,
+            frame_sample: DEFAULT_FRAME_SAMPLE,
+            status: None,
+        }
+    }
+
+    /// Overrides the reconnect policy.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Sends websocket ping frames at the given interval to keep idle connections alive.
+    pub fn ping_interval(mut self, period: Duration) -> Self {
+        self.ping_interval = Some(period);
+        self
+    }
+
+    /// Records every raw text frame with its receive timestamp to a capture file.
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// Logs one raw frame in every `every` at debug level (default 1000); 0 disables
+    /// sampling. Every frame is still logged at trace level.
+    pub fn sample_frames(mut self, every: u64) -> Self {
+        self.frame_sample = every;
+        self
+    }
+
+    /// Reports whether the websocket is connected to `status`.
+    pub fn status(mut self, status: PipelineStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Returns the protocol driving this source.
+    pub fn protocol(&self) -> &P {
+        &self.protocol
+    }
+
+    async fn stream(&self, tx: Sender<MessageBatch<P::Message>>) -> Result<()> {
+        let mut recorder = match &self.record_path {
+            Some(path) => Some(CaptureRecorder::create(path, self.protocol.name()).await?),
+            None => None,
+        };
+
+        let mut attempt = 0u32;
+        loop {
+            let span = info_span!(
+                "websocket",
+                connector = self.protocol.name(),
+                connection = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+                url = %redact_credentials(self.protocol.url()),
+                subscription = %self.protocol.subscribe_payload().unwrap_or_default(),
+            );
+            let started = Instant::now();
+            let mut delivered = false;
+            let session = self
+                .run_session(&tx, &mut recorder, &mut delivered)
+                .instrument(span)
+                .await;
+            self.set_connected(false);
+            match session {
+                Ok(SessionEnd::ChannelClosed) => {
+                    info!("Pipeline channel closed, stopping websocket source");
+                    return Ok(());
+                }
+                Ok(SessionEnd::Fatal(reason)) => {
+                    bail!("{} websocket terminated: {reason}", self.protocol.name());
+                }
+                Ok(SessionEnd::Disconnected) => {
+                    if self.reconnect.max_retries == Some(0) {
+                        return Ok(());
+                    }
+                    attempt = match delivered || started.elapsed() >= STABLE_SESSION {
+                        true => 1,
+                        false => attempt + 1,
+                    };
+                    if self.reconnect.max_retries.is_some_and(|max| attempt > max) {
+                        bail!(
+                            "{} websocket dropped {attempt} times in a row without delivering data",
+                            self.protocol.name()
+                        );
+                    }
+                }
+                Err(err) => {
+                    attempt += 1;
+                    if self.reconnect.max_retries.is_some_and(|max| attempt > max) {
+                        return Err(err);
+                    }
+                    warn!("WebSocket connection failed: {err}");
+                }
+            }
+
+            let backoff = self.reconnect.backoff(attempt);
+            info!(attempt = attempt, backoff = ?backoff, "Reconnecting websocket");
+            sleep(backoff).await;
+        }
+    }
+
+    async fn run_session(
+        &self,
+        tx: &Sender<MessageBatch<P::Message>>,
+        recorder: &mut Option<CaptureRecorder>,
+        delivered: &mut bool,
+    ) -> Result<SessionEnd> {
+        info!("Try connect to websocket");
+        let (ws_stream, _) = connect_async(self.protocol.url()).await?;
+        info!("WebSocket connected");
+        self.set_connected(true);
+        let (mut write, mut read) = ws_stream.split();
+
+        if let Some(payload) = self.protocol.auth_payload() {
+            info!("Authenticating");
+            write.send(WsMessage::Text(payload)).await?;
+        }
+        if let Some(payload) = self.protocol.subscribe_payload() {
+            write.send(WsMessage::Text(payload)).await?;
+        }
+
+        let mut ping = self.ping_interval.map(periodic);
+        let (mut heartbeat, heartbeat_payload) = match self.protocol.heartbeat() {
+            Some((period, payload)) => (Some(periodic(period)), payload),
+            None => (None, String::new()),
+        };
+
+        info!("Watching read stream...");
+        let mut frames = 0u64;
+        loop {
+            tokio::select! {
+                message = read.next() => {
+                    let Some(message) = message else {
+                        info!("WebSocket stream ended");
+                        return Ok(SessionEnd::Disconnected);
+                    };
+                    match message {
+                        Ok(WsMessage::Text(text)) => {
+                            frames += 1;
+                            trace!(frame = %text, "Received frame");
+                            if self.frame_sample > 0 && frames.is_multiple_of(self.frame_sample) {
+                                debug!(frames, frame = %truncate(&text), "Sampled frame");
+                            }
+                            if let Some(recorder) = recorder.as_mut()
+                                && let Err(err) = recorder.record(&text).await
+                            {
+                                warn!("Failed to record frame: {err}");
+                            }
+                            if let Some(end) = self.dispatch(&text, tx, delivered).await {
+                                return Ok(end);
+                            }
+                        }
+                        Ok(WsMessage::Binary(_)) => debug!("Binary message ignored"),
+                        Ok(WsMessage::Ping(data)) => {
+                            debug!("Received ping, sending pong");
+                            if write.send(WsMessage::Pong(data)).await.is_err() {
+                                return Ok(SessionEnd::Disconnected);
+                            }
+                        }
+                        Ok(WsMessage::Pong(_)) => debug!("Received pong"),
+                        Ok(WsMessage::Close(frame)) => {
+                            info!("Received close message: {:?}", frame);
+                            return Ok(SessionEnd::Disconnected);
+                        }
+                        Ok(WsMessage::Frame(_)) => {}
+                        Err(err) => {
+                            error!("WebSocket error: {err}");
+                            return Ok(SessionEnd::Disconnected);
+                        }
+                    }
+                }
+                _ = tick(&mut ping) => {
+                    if write.send(WsMessage::Ping(Vec::new())).await.is_err() {
+                        return Ok(SessionEnd::Disconnected);
+                    }
+                }
+                _ = tick(&mut heartbeat) => {
+                    if write.send(WsMessage::Text(heartbeat_payload.clone())).await.is_err() {
+                        return Ok(SessionEnd::Disconnected);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses a frame, reacts to control messages and forwards the batch downstream,
+    /// setting `delivered` once it carried market data.
+    async fn dispatch(
+        &self,
+        text: &str,
+        tx: &Sender<MessageBatch<P::Message>>,
+        delivered: &mut bool,
+    ) -> Option<SessionEnd> {
+        let messages = match self.protocol.parse(text) {
+            Ok(messages) => messages,
+            Err(err) => {
+                warn!(error = %err, frame = %truncate(text), "Failed to parse frame");
+                return None;
+            }
+        };
+
+        let mut fatal = None;
+        for message in &messages {
+            match self.protocol.classify(message) {
+                Control::Data => *delivered = true,
+                Control::Status(status) => info!("{} status: {status}", self.protocol.name()),
+                Control::Error(reason) => {
+                    warn!("{} error: {reason}", self.protocol.name());
+                    if let Some(status) = &self.status {
+                        status.record_error(&reason);
+                    }
+                }
+                Control::Fatal(reason) => fatal = Some(reason),
+            }
+        }
+
+        if !messages.is_empty() && tx.send(messages).await.is_err() {
+            return Some(SessionEnd::ChannelClosed);
+        }
+        fatal.map(SessionEnd::Fatal)
+    }
+
+    fn set_connected(&self, connected: bool) {
+        if let Some(status) = &self.status {
+            status.set_connected(connected);
+        }
+    }
+}
+
+/// Shortens a frame for logging, noting how much was cut.
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_LOGGED_FRAME) {
+        Some((end, _)) => format!("{}... ({} bytes)", &text[..end], text.len()),
+        None => text.to_string(),
+    }
+}
+
+impl<P: Protocol> MessageSource<P::Message> for WebSocketSource<P> {
+    fn run<'a>(
+        &'a mut self,
+        tx: Sender<MessageBatch<P::Message>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move { self.stream(tx).await })
+    }
+}
+
+fn periodic(period: Duration) -> Interval {
+    interval_at(Instant::now() + period, period)
+}
+
+/// Waits for the next tick of an optional timer, never resolving when it is unset.
+async fn tick(timer: &mut Option<Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
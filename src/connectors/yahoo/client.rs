@@ -1,17 +1,186 @@
-use super::types::{IncomeStatementRow, YahooMessage};
+use super::pool::{
+    ClientPool, ClientSelection, HealthPolicy, PooledClient, ProxyHealth, proxy_label,
+};
+use super::symbols::resume_index;
+use super::types::{
+    BarInterval, Dividend, IncomeStatementRow, PriceBar, Split, StatementFrequency, YahooMessage,
+};
 use crate::{
+    connectors::rate_limit::HttpStatus,
     connectors::yahoo::types::{BalanceSheetRow, CalendarDateType, CalendarEntry, CashflowRow},
-    core::{MessageBatch, MessageSource},
+    core::{Checkpoint, MessageBatch, MessageSource, TrailingCheckpoint},
+    secret::redact_credentials,
 };
+use anyhow::{Context, anyhow, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::{StreamExt, stream};
 use std::pin::Pin;
-use tokio::time::{Duration, sleep};
-use tracing::debug;
-use yfinance_rs::{Ticker, YfClient};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::time::{Duration, Instant};
+use tracing::{Instrument, debug, info, info_span, warn};
+use yfinance_rs::{Action, Interval, Ticker, YfClient, YfError};
+
+/// Number of completed symbols between client health summaries.
+const HEALTH_LOG_EVERY: usize = 100;
+
+/// What to do when fetching a dataset for a symbol fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Abort the whole run on the first failure
+    FailFast,
+    /// Record the failure in the report and continue
+    #[default]
+    SkipAndReport,
+    /// Retry up to `n` more times, then record the failure and continue
+    Retry(u32),
+}
+
+impl ErrorPolicy {
+    /// Sends `request`, passing the attempt number from 0, until it succeeds or the
+    /// retries of [`ErrorPolicy::Retry`] are used up; returns the last error.
+    pub async fn run<T, F, Fut>(self, mut request: F) -> anyhow::Result<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let retries = match self {
+            ErrorPolicy::Retry(n) => n,
+            _ => 0,
+        };
+
+        let mut attempt = 0;
+        loop {
+            match request(attempt).await {
+                Err(e) if attempt < retries => {
+                    attempt += 1;
+                    warn!(
+                        attempt = attempt,
+                        error = %redact_credentials(&format!("{e:#}")),
+                        "Retrying Yahoo request"
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Whether a failure left after retries aborts the whole run.
+    pub fn aborts_run(self) -> bool {
+        self == ErrorPolicy::FailFast
+    }
+}
+
+/// A dataset that could not be fetched for a symbol.
+#[derive(Debug, Clone)]
+pub struct FetchFailure {
+    pub symbol: String,
+    /// Dataset name, e.g. `annual_income_statement` or `calendars`
+    pub dataset: String,
+    pub error: String,
+}
+
+impl FetchFailure {
+    /// Records `error` with any proxy credentials it echoes redacted.
+    pub fn new(symbol: &str, dataset: String, error: &anyhow::Error) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            dataset,
+            error: redact_credentials(&format!("{error:#}")),
+        }
+    }
+}
+
+/// Outcome of a run: how many symbols were processed and what failed.
+#[derive(Debug, Clone, Default)]
+pub struct FetchReport {
+    pub symbols: usize,
+    pub failures: Vec<FetchFailure>,
+}
+
+impl FetchReport {
+    /// Distinct symbols with at least one failed dataset.
+    pub fn failed_symbols(&self) -> Vec<&str> {
+        let mut symbols: Vec<&str> = self.failures.iter().map(|f| f.symbol.as_str()).collect();
+        symbols.dedup();
+        symbols
+    }
+}
+
+/// Date range and bar length of the price history to fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HistoryRange {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    interval: BarInterval,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dataset {
+    IncomeStatement(StatementFrequency),
+    BalanceSheet(StatementFrequency),
+    Cashflow(StatementFrequency),
+    History(HistoryRange),
+    Calendars,
+}
 
+impl Dataset {
+    /// Every statement at each of the given frequencies, then the price history if
+    /// requested, then calendars.
+    ///
+    /// TTM rows are stored as quarters too, so quarterly statements are only fetched once
+    /// when both are requested.
+    fn all(frequencies: &[StatementFrequency], history: Option<HistoryRange>) -> Vec<Dataset> {
+        let with_ttm = frequencies.contains(&StatementFrequency::Ttm);
+        let mut datasets: Vec<Dataset> = Vec::new();
+        for &frequency in frequencies {
+            if with_ttm && frequency == StatementFrequency::Quarterly {
+                continue;
+            }
+            for dataset in [
+                Dataset::IncomeStatement(frequency),
+                Dataset::BalanceSheet(frequency),
+                Dataset::Cashflow(frequency),
+            ] {
+                if !datasets.contains(&dataset) {
+                    datasets.push(dataset);
+                }
+            }
+        }
+        datasets.extend(history.map(Dataset::History));
+        datasets.push(Dataset::Calendars);
+        datasets
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Dataset::IncomeStatement(frequency) => {
+                format!("{}_income_statement", frequency.as_str())
+            }
+            Dataset::BalanceSheet(frequency) => format!("{}_balance_sheet", frequency.as_str()),
+            Dataset::Cashflow(frequency) => format!("{}_cashflow", frequency.as_str()),
+            Dataset::History(_) => "price_history".to_string(),
+            Dataset::Calendars => "calendars".to_string(),
+        }
+    }
+}
+
+/// Fetches statements and calendars for a list of symbols from Yahoo Finance,
+/// directly or through a pool of proxies.
 pub struct YahooClient {
-    client: YfClient,
+    pool: ClientPool,
     symbols: Vec<String>,
-    timeout_ms: u64,
+    /// Statement frequencies fetched for each symbol
+    frequencies: Vec<StatementFrequency>,
+    /// Price bars and corporate actions fetched for each symbol, if any
+    history: Option<HistoryRange>,
+    /// Number of symbols fetched at once
+    concurrency: usize,
+    error_policy: ErrorPolicy,
+    /// Last completed symbol of an interrupted run
+    checkpoint: Option<Checkpoint>,
+    /// Per-client request interval, applied to proxies added later
+    interval: Duration,
+    report: FetchReport,
 }
 
 impl MessageSource<YahooMessage> for YahooClient {
@@ -20,183 +189,438 @@ impl MessageSource<YahooMessage> for YahooClient {
         tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
     ) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>> {
         Box::pin(async move {
-            for symbol in &self.symbols {
-                self.fetch_all_statements(symbol, tx.clone()).await?;
-                sleep(Duration::from_millis(self.timeout_ms)).await;
+            let start = match &self.checkpoint {
+                Some(checkpoint) => resume_index(&self.symbols, checkpoint).await?,
+                None => 0,
+            };
+
+            let mut report = FetchReport::default();
+            let mut trailing = self.checkpoint.clone().map(|checkpoint| {
+                let in_flight = TrailingCheckpoint::in_flight(tx.max_capacity(), self.concurrency);
+                TrailingCheckpoint::new(checkpoint, in_flight)
+            });
+            let sent = AtomicUsize::new(0);
+            {
+                // Symbols are fetched concurrently but completed in order, so the
+                // checkpoint always marks a contiguous prefix of the list.
+                let this = &*self;
+                let sent = &sent;
+                let mut fetches = stream::iter(start..this.symbols.len())
+                    .map(|index| {
+                        let symbol = this.symbols[index].as_str();
+                        let tx = tx.clone();
+                        async move { (symbol, this.fetch_all_statements(symbol, tx, sent).await) }
+                    })
+                    .buffered(this.concurrency);
+
+                while let Some((symbol, result)) = fetches.next().await {
+                    report.failures.extend(result?);
+                    report.symbols += 1;
+                    if let Some(trailing) = &mut trailing {
+                        trailing
+                            .advance(symbol, sent.load(Ordering::SeqCst))
+                            .await?;
+                    }
+                    if report.symbols % HEALTH_LOG_EVERY == 0 {
+                        this.pool.log_health();
+                    }
+                }
+            }
+
+            if let Some(trailing) = &mut trailing {
+                trailing.clear().await?;
             }
+
+            info!(
+                symbols = report.symbols,
+                failures = report.failures.len(),
+                failed_symbols = report.failed_symbols().len(),
+                "Finished fetching Yahoo data"
+            );
+            for failure in &report.failures {
+                warn!(
+                    symbol = %failure.symbol,
+                    dataset = %failure.dataset,
+                    error = %failure.error,
+                    "Yahoo dataset not fetched"
+                );
+            }
+            self.pool.log_health();
+            self.report = report;
             Ok(())
         })
     }
 }
 
 impl YahooClient {
+    /// Creates a direct client sending at most one request every `timeout_ms`, one symbol
+    /// at a time.
     pub fn new(symbols: Vec<String>, timeout_ms: u64) -> Self {
+        let interval = Duration::from_millis(timeout_ms);
         Self {
-            client: YfClient::default(),
+            pool: ClientPool::direct(interval),
             symbols,
-            timeout_ms,
+            frequencies: vec![StatementFrequency::Quarterly],
+            history: None,
+            concurrency: 1,
+            error_policy: ErrorPolicy::default(),
+            checkpoint: None,
+            interval,
+            report: FetchReport::default(),
         }
     }
 
-    async fn fetch_all_statements(
-        &self,
-        symbol: &str,
-        tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
-    ) -> anyhow::Result<()> {
-        self.fetch_income_statement(symbol, tx.clone()).await?;
-        sleep(Duration::from_millis(self.timeout_ms)).await;
+    /// Adds a client per proxy alongside the direct connection, each with its own rate
+    /// limit. Unless [`YahooClient::selection`] picked a strategy, clients take turns in
+    /// round-robin order.
+    pub fn proxies(mut self, proxies: Vec<String>) -> anyhow::Result<Self> {
+        for proxy in proxies {
+            let label = proxy_label(&proxy);
+            let client = YfClient::builder().proxy(&proxy).build().map_err(|e| {
+                // Client errors may echo the proxy URL, credentials included
+                let reason = redact_credentials(&e.to_string());
+                anyhow!("Failed to create client with proxy {label}: {reason}")
+            })?;
+            self.pool.push(label, client, self.interval);
+        }
+        Ok(self)
+    }
 
-        self.fetch_balance_sheet(symbol, tx.clone()).await?;
-        sleep(Duration::from_millis(self.timeout_ms)).await;
+    /// Overrides how the next client is picked.
+    pub fn selection(mut self, selection: ClientSelection) -> Self {
+        self.pool.set_selection(selection);
+        self
+    }
 
-        self.fetch_cashflow(symbol, tx.clone()).await?;
-        sleep(Duration::from_millis(self.timeout_ms)).await;
+    /// Statement frequencies to fetch for each symbol; quarterly only by default.
+    pub fn frequencies(mut self, frequencies: Vec<StatementFrequency>) -> Self {
+        self.frequencies = frequencies;
+        self
+    }
 
-        self.fetch_calendars(symbol, tx.clone()).await?;
-        sleep(Duration::from_millis(self.timeout_ms)).await;
-        Ok(())
+    /// Also fetches unadjusted price bars, dividends and splits between `start` and `end`.
+    pub fn history(
+        mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: BarInterval,
+    ) -> Self {
+        self.history = Some(HistoryRange {
+            start,
+            end,
+            interval,
+        });
+        self
     }
 
-    async fn fetch_income_statement(
-        &self,
-        symbol: &str,
-        tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
-    ) -> anyhow::Result<()> {
-        debug!("Fetching income statement data for {symbol}");
-        let ticker = Ticker::new(&self.client, symbol);
-        let stmt = ticker
-            .quarterly_income_stmt(None)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch income statement for {symbol}: {e}"))?;
+    /// Overrides when failing clients are quarantined and for how long.
+    pub fn health_policy(mut self, policy: HealthPolicy) -> Self {
+        self.pool.set_health_policy(policy);
+        self
+    }
 
-        let messages: Vec<YahooMessage> = stmt
-            .into_iter()
-            .map(|row| {
-                YahooMessage::IncomeStatement(IncomeStatementRow {
-                    symbol: symbol.to_string(),
-                    inner: row,
-                })
-            })
-            .collect();
+    /// Overrides what happens when a dataset fails to load.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
 
-        tx.send(messages)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send income statement messages: {e}"))?;
-        Ok(())
+    /// Gives every client, including proxies added afterwards, a budget of
+    /// `requests_per_second` and bursts of up to `burst`. The rate must be positive.
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> anyhow::Result<Self> {
+        if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+            bail!(
+                "rate limit must be a positive number of requests per second, got {requests_per_second}"
+            );
+        }
+        self.pool.set_rate_limit(requests_per_second, burst);
+        Ok(self)
     }
 
-    async fn fetch_balance_sheet(
-        &self,
-        symbol: &str,
-        tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
-    ) -> anyhow::Result<()> {
-        debug!("Fetching balance sheet data for {symbol}");
-        let ticker = Ticker::new(&self.client, symbol);
-        let stmt = ticker
-            .quarterly_balance_sheet(None)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch balance sheet for {symbol}: {e}"))?;
+    /// Fetches up to `symbols` symbols at once; each request waits for its client's budget.
+    pub fn concurrency(mut self, symbols: usize) -> Self {
+        self.concurrency = symbols.max(1);
+        self
+    }
 
-        let messages: Vec<YahooMessage> = stmt
-            .into_iter()
-            .map(|row| {
-                YahooMessage::BalanceSheet(BalanceSheetRow {
-                    symbol: symbol.to_string(),
-                    inner: row,
-                })
-            })
-            .collect();
+    /// Skips symbols up to the last completed one of a previous run. Symbols are saved
+    /// once their statements must have reached the sink (see [`TrailingCheckpoint`]), so a
+    /// resumed run re-fetches the last few symbols. The checkpoint is cleared on
+    /// completion.
+    pub fn checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
 
-        tx.send(messages)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send balance sheet messages: {e}"))?;
-        Ok(())
+    /// Report of the last completed run.
+    pub fn report(&self) -> &FetchReport {
+        &self.report
     }
 
-    async fn fetch_cashflow(
+    /// Request statistics of the direct connection and every proxy.
+    pub fn health(&self) -> Vec<ProxyHealth> {
+        self.pool.health()
+    }
+
+    /// Fetches every dataset of a symbol, returning the failures the policy tolerated.
+    async fn fetch_all_statements(
         &self,
         symbol: &str,
         tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
-    ) -> anyhow::Result<()> {
-        debug!("Fetching cashflow data for {symbol}");
-        let ticker = Ticker::new(&self.client, symbol);
-        let stmt = ticker
-            .quarterly_cashflow(None)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch cashflow for {symbol}: {e}"))?;
+        sent: &AtomicUsize,
+    ) -> anyhow::Result<Vec<FetchFailure>> {
+        let mut failures = Vec::new();
+        for dataset in Dataset::all(&self.frequencies, self.history) {
+            let messages = match self.fetch_with_policy(symbol, dataset).await {
+                Ok(messages) => messages,
+                Err(e) if self.error_policy.aborts_run() => return Err(e),
+                Err(e) => {
+                    failures.push(FetchFailure::new(symbol, dataset.name(), &e));
+                    continue;
+                }
+            };
 
-        let messages: Vec<YahooMessage> = stmt
-            .into_iter()
-            .map(|row| {
-                YahooMessage::Cashflow(CashflowRow {
-                    symbol: symbol.to_string(),
-                    inner: row,
-                })
-            })
-            .collect();
-
-        tx.send(messages)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send cashflow messages: {e}"))?;
-        Ok(())
+            if !messages.is_empty() {
+                tx.send(messages)
+                    .await
+                    .map_err(|e| anyhow!("Failed to send {} messages: {e}", dataset.name()))?;
+                sent.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        Ok(failures)
     }
 
-    async fn fetch_calendars(
+    async fn fetch_with_policy(
         &self,
         symbol: &str,
-        tx: tokio::sync::mpsc::Sender<MessageBatch<YahooMessage>>,
-    ) -> anyhow::Result<()> {
-        debug!("Fetching cashflow data for {symbol}");
-        let ticker = Ticker::new(&self.client, symbol);
-        let calendars = ticker
-            .calendar()
+        dataset: Dataset,
+    ) -> anyhow::Result<Vec<YahooMessage>> {
+        let span = info_span!("yahoo_fetch", symbol = %symbol, dataset = %dataset.name());
+        self.error_policy
+            .run(|_| async {
+                let (idx, pooled) = self.pool.next();
+                debug!(client = %pooled.label, "Fetching Yahoo data");
+                let mut latency = Duration::ZERO;
+                let result = fetch(pooled, symbol, dataset, &mut latency).await;
+                // The client answered for symbol-level errors, so only its own count
+                let client_ok = !result.as_ref().is_err_and(is_client_failure);
+                self.pool.record(idx, client_ok, latency);
+                result
+            })
+            .instrument(span)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch cashflow for {symbol}: {e}"))?;
+    }
+}
 
-        let earnings: Vec<YahooMessage> = calendars
-            .earnings_dates
-            .into_iter()
-            .map(|row| {
-                YahooMessage::Calendar(CalendarEntry {
-                    symbol: symbol.to_string(),
-                    date_type: CalendarDateType::Earnings,
-                    date: row.naive_utc(),
+/// Fetches one dataset through a pooled client, within its rate limit, storing how long
+/// the request took once sent in `latency`.
+async fn fetch(
+    pooled: &PooledClient,
+    symbol: &str,
+    dataset: Dataset,
+    latency: &mut Duration,
+) -> anyhow::Result<Vec<YahooMessage>> {
+    let ticker = Ticker::new(&pooled.client, symbol);
+    let limiter = &pooled.limiter;
+
+    let messages: Vec<YahooMessage> = match dataset {
+        Dataset::IncomeStatement(frequency) => {
+            let rows = match frequency {
+                StatementFrequency::Annual => {
+                    limiter.run(timed(ticker.income_stmt(None), latency)).await
+                }
+                // TTM is rolled up from quarters downstream
+                StatementFrequency::Quarterly | StatementFrequency::Ttm => {
+                    limiter
+                        .run(timed(ticker.quarterly_income_stmt(None), latency))
+                        .await
+                }
+            };
+            rows.with_context(|| format!("Failed to fetch income statement for {symbol}"))?
+                .into_iter()
+                .map(|row| {
+                    YahooMessage::IncomeStatement(IncomeStatementRow {
+                        symbol: symbol.to_string(),
+                        frequency,
+                        inner: row,
+                    })
                 })
-            })
-            .collect();
+                .collect()
+        }
+        Dataset::BalanceSheet(frequency) => {
+            let rows = match frequency {
+                StatementFrequency::Annual => {
+                    limiter
+                        .run(timed(ticker.balance_sheet(None), latency))
+                        .await
+                }
+                StatementFrequency::Quarterly | StatementFrequency::Ttm => {
+                    limiter
+                        .run(timed(ticker.quarterly_balance_sheet(None), latency))
+                        .await
+                }
+            };
+            rows.with_context(|| format!("Failed to fetch balance sheet for {symbol}"))?
+                .into_iter()
+                .map(|row| {
+                    YahooMessage::BalanceSheet(BalanceSheetRow {
+                        symbol: symbol.to_string(),
+                        frequency,
+                        inner: row,
+                    })
+                })
+                .collect()
+        }
+        Dataset::Cashflow(frequency) => {
+            let rows = match frequency {
+                StatementFrequency::Annual => {
+                    limiter.run(timed(ticker.cashflow(None), latency)).await
+                }
+                StatementFrequency::Quarterly | StatementFrequency::Ttm => {
+                    limiter
+                        .run(timed(ticker.quarterly_cashflow(None), latency))
+                        .await
+                }
+            };
+            rows.with_context(|| format!("Failed to fetch cashflow for {symbol}"))?
+                .into_iter()
+                .map(|row| {
+                    YahooMessage::Cashflow(CashflowRow {
+                        symbol: symbol.to_string(),
+                        frequency,
+                        inner: row,
+                    })
+                })
+                .collect()
+        }
+        Dataset::History(range) => {
+            let interval = match range.interval {
+                BarInterval::Hour => Interval::I1h,
+                BarInterval::Day => Interval::D1,
+                BarInterval::Week => Interval::W1,
+                BarInterval::Month => Interval::M1,
+            };
+            // Unadjusted prices; splits are stored alongside to adjust in the database
+            let history = limiter
+                .run(timed(
+                    ticker
+                        .history_builder()
+                        .between(range.start, range.end)
+                        .interval(interval)
+                        .auto_adjust(false)
+                        .actions(true)
+                        .fetch_full(),
+                    latency,
+                ))
+                .await
+                .with_context(|| format!("Failed to fetch price history for {symbol}"))?;
 
-        let dividend_payment_dates: Vec<YahooMessage> = calendars
-            .dividend_payment_date
-            .into_iter()
-            .map(|row| {
-                YahooMessage::Calendar(CalendarEntry {
-                    symbol: symbol.to_string(),
-                    date_type: CalendarDateType::DividendPayment,
-                    date: row.naive_utc(),
+            let mut messages: Vec<YahooMessage> = history
+                .candles
+                .into_iter()
+                .filter_map(|candle| {
+                    PriceBar::new(
+                        symbol,
+                        range.interval,
+                        candle.ts,
+                        [
+                            candle.open.amount(),
+                            candle.high.amount(),
+                            candle.low.amount(),
+                            candle.close.amount(),
+                        ],
+                        candle.volume,
+                    )
                 })
-            })
-            .collect();
+                .map(YahooMessage::PriceBar)
+                .collect();
+
+            messages.extend(
+                history
+                    .actions
+                    .into_iter()
+                    .filter_map(|action| match action {
+                        Action::Dividend { ts, amount } => {
+                            Dividend::new(symbol, ts, amount.amount()).map(YahooMessage::Dividend)
+                        }
+                        Action::Split {
+                            ts,
+                            numerator,
+                            denominator,
+                        } => {
+                            Split::new(symbol, ts, numerator, denominator).map(YahooMessage::Split)
+                        }
+                        _ => None,
+                    }),
+            );
+            messages
+        }
+        Dataset::Calendars => {
+            let calendars = limiter
+                .run(timed(ticker.calendar(), latency))
+                .await
+                .with_context(|| format!("Failed to fetch calendars for {symbol}"))?;
 
-        let ex_dividend_dates: Vec<YahooMessage> = calendars
-            .ex_dividend_date
-            .into_iter()
-            .map(|row| {
+            let entry = |date_type: CalendarDateType, date: NaiveDateTime| {
                 YahooMessage::Calendar(CalendarEntry {
                     symbol: symbol.to_string(),
-                    date_type: CalendarDateType::ExDividend,
-                    date: row.naive_utc(),
+                    date_type,
+                    date,
                 })
-            })
-            .collect();
+            };
+
+            let mut messages = Vec::new();
+            messages.extend(
+                calendars
+                    .earnings_dates
+                    .into_iter()
+                    .map(|row| entry(CalendarDateType::Earnings, row.naive_utc())),
+            );
+            messages.extend(
+                calendars
+                    .dividend_payment_date
+                    .into_iter()
+                    .map(|row| entry(CalendarDateType::DividendPayment, row.naive_utc())),
+            );
+            messages.extend(
+                calendars
+                    .ex_dividend_date
+                    .into_iter()
+                    .map(|row| entry(CalendarDateType::ExDividend, row.naive_utc())),
+            );
+            messages
+        }
+    };
+    Ok(messages)
+}
 
-        let mut messages: Vec<YahooMessage> = Vec::new();
-        messages.extend(earnings);
-        messages.extend(dividend_payment_dates);
-        messages.extend(ex_dividend_dates);
+/// Whether a failed request points at the client rather than the symbol: a transport
+/// error, an HTTP error status or rate limiting. Unknown tickers (404) and missing or
+/// malformed data are symbol-level and do not count against the client.
+pub fn is_client_failure(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<YfError>() {
+        Some(YfError::Http(e)) => e.status().is_none_or(|status| status.as_u16() != 404),
+        Some(e) => e.http_status().is_some_and(|status| status != 404),
+        None => false,
+    }
+}
 
-        tx.send(messages)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send calendar messages: {e}"))?;
-        Ok(())
+impl HttpStatus for YfError {
+    fn http_status(&self) -> Option<u16> {
+        match self {
+            YfError::RateLimited { .. } => Some(429),
+            YfError::NotFound { .. } => Some(404),
+            YfError::Status { status, .. } | YfError::ServerError { status, .. } => Some(*status),
+            YfError::Http(e) => e.status().map(|status| status.as_u16()),
+            _ => None,
+        }
     }
 }
+
+/// Runs a request and stores its duration, excluding any wait before it is polled.
+async fn timed<T>(request: impl Future<Output = T>, latency: &mut Duration) -> T {
+    let started = Instant::now();
+    let output = request.await;
+    *latency = started.elapsed();
+    output
+}
@@ -1,8 +1,8 @@
 pub mod client;
-pub mod proxy_client;
+pub mod pool;
 pub mod symbols;
 pub mod types;
 
-pub use client::YahooClient;
-pub use proxy_client::ProxyYahooClient;
-pub use types::YahooMessage;
+pub use client::{ErrorPolicy, FetchFailure, FetchReport, YahooClient};
+pub use pool::{ClientPool, ClientSelection, HealthPolicy, ProxyHealth};
+pub use types::{BarInterval, StatementFrequency, YahooMessage};
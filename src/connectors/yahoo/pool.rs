@@ -0,0 +1,291 @@
+//! Pool of Yahoo clients (direct and proxied) with pluggable selection and health tracking.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+use yfinance_rs::YfClient;
+
+use crate::connectors::rate_limit::RateLimiter;
+
+/// How the next client is picked for each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientSelection {
+    /// Always use the first client (the direct connection)
+    Single,
+    /// Rotate through all clients in order
+    #[default]
+    RoundRobin,
+    /// Rotate in proportion to each client's success rate
+    HealthWeighted,
+}
+
+/// When a failing client is taken out of rotation and for how long.
+#[derive(Debug, Clone)]
+pub struct HealthPolicy {
+    /// Consecutive failures that quarantine a client
+    pub max_consecutive_failures: u32,
+    /// Time a quarantined client sits out before a single probe request is let through
+    pub cooldown: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            max_consecutive_failures: 3,
+            cooldown: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Snapshot of a client's request statistics.
+#[derive(Debug, Clone)]
+pub struct ProxyHealth {
+    pub label: String,
+    pub successes: u64,
+    pub failures: u64,
+    /// Mean latency of all requests, successful or not
+    pub avg_latency: Duration,
+    pub quarantined: bool,
+    /// Requests per second the client may currently send
+    pub rate: f64,
+}
+
+#[derive(Default)]
+struct Health {
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    total_latency: Duration,
+    quarantined_until: Option<Instant>,
+}
+
+/// A client with its request budget and health statistics.
+pub struct PooledClient {
+    /// Proxy address without credentials, or `direct`
+    pub label: String,
+    pub(crate) client: YfClient,
+    pub(crate) limiter: RateLimiter,
+    health: Mutex<Health>,
+}
+
+impl PooledClient {
+    /// Laplace-smoothed success rate, so untried clients start at 0.5.
+    fn score(&self) -> f64 {
+        let health = self.health.lock().unwrap();
+        (health.successes as f64 + 1.0) / ((health.successes + health.failures) as f64 + 2.0)
+    }
+
+    /// Whether the client may take a request now. A client whose cooldown has passed
+    /// is let through once as a probe and stays quarantined until the probe succeeds.
+    fn try_acquire(&self, now: Instant, cooldown: Duration) -> bool {
+        let mut health = self.health.lock().unwrap();
+        let quarantined_until = health.quarantined_until;
+        match quarantined_until {
+            None => true,
+            Some(until) if until <= now => {
+                health.quarantined_until = Some(now + cooldown);
+                info!(client = %self.label, "Probing quarantined client");
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn quarantined_until(&self) -> Option<Instant> {
+        self.health.lock().unwrap().quarantined_until
+    }
+
+    fn snapshot(&self) -> ProxyHealth {
+        let health = self.health.lock().unwrap();
+        let requests = (health.successes + health.failures).max(1);
+        ProxyHealth {
+            label: self.label.clone(),
+            successes: health.successes,
+            failures: health.failures,
+            avg_latency: health.total_latency / requests as u32,
+            quarantined: health.quarantined_until.is_some(),
+            rate: self.limiter.rate(),
+        }
+    }
+}
+
+/// The direct connection and any proxies, with how requests are spread among them.
+pub struct ClientPool {
+    clients: Vec<PooledClient>,
+    /// Explicitly chosen selection; defaults to round-robin once proxies are added
+    selection: Option<ClientSelection>,
+    health_policy: HealthPolicy,
+    /// Requests per second and burst of every client, replacing the per-client interval
+    rate_limit: Option<(f64, u32)>,
+    counter: AtomicUsize,
+    /// Running weights of the smooth weighted round-robin
+    current_weights: Mutex<Vec<f64>>,
+}
+
+impl ClientPool {
+    /// Creates a pool holding only the direct connection.
+    pub fn direct(interval: Duration) -> Self {
+        let mut pool = Self {
+            clients: Vec::new(),
+            selection: None,
+            health_policy: HealthPolicy::default(),
+            rate_limit: None,
+            counter: AtomicUsize::new(0),
+            current_weights: Mutex::new(Vec::new()),
+        };
+        pool.push("direct".to_string(), YfClient::default(), interval);
+        pool
+    }
+
+    /// Adds a client sending at most one request every `interval`, or within the pool's
+    /// rate limit once one is set.
+    pub fn push(&mut self, label: String, client: YfClient, interval: Duration) {
+        let limiter = match self.rate_limit {
+            Some((requests_per_second, burst)) => RateLimiter::new(requests_per_second, burst),
+            None => RateLimiter::per_interval(interval),
+        };
+        self.clients.push(PooledClient {
+            label,
+            client,
+            limiter,
+            health: Mutex::new(Health::default()),
+        });
+        self.current_weights.get_mut().unwrap().push(0.0);
+    }
+
+    pub fn set_selection(&mut self, selection: ClientSelection) {
+        self.selection = Some(selection);
+    }
+
+    /// The chosen selection, or round-robin once the pool holds more than one client.
+    pub fn selection(&self) -> ClientSelection {
+        match self.selection {
+            Some(selection) => selection,
+            None if self.clients.len() > 1 => ClientSelection::RoundRobin,
+            None => ClientSelection::Single,
+        }
+    }
+
+    pub fn set_health_policy(&mut self, policy: HealthPolicy) {
+        self.health_policy = policy;
+    }
+
+    /// Gives every client, including those added later, its own budget of
+    /// `requests_per_second` with bursts of up to `burst`.
+    pub fn set_rate_limit(&mut self, requests_per_second: f64, burst: u32) {
+        self.rate_limit = Some((requests_per_second, burst));
+        for pooled in &mut self.clients {
+            pooled.limiter = RateLimiter::new(requests_per_second, burst);
+        }
+    }
+
+    /// Picks the client for the next request, skipping quarantined clients.
+    ///
+    /// When every client is quarantined the one whose cooldown ends first is used.
+    pub fn next(&self) -> (usize, &PooledClient) {
+        let now = Instant::now();
+        let cooldown = self.health_policy.cooldown;
+        let selection = self.selection();
+        let candidates = match selection {
+            ClientSelection::Single => 1,
+            _ => self.clients.len(),
+        };
+
+        for _ in 0..candidates {
+            let idx = match selection {
+                ClientSelection::Single => 0,
+                ClientSelection::RoundRobin => {
+                    self.counter.fetch_add(1, Ordering::Relaxed) % self.clients.len()
+                }
+                ClientSelection::HealthWeighted => self.next_weighted(),
+            };
+            if self.clients[idx].try_acquire(now, cooldown) {
+                return (idx, &self.clients[idx]);
+            }
+        }
+
+        let idx = (0..candidates)
+            .min_by_key(|&idx| self.clients[idx].quarantined_until())
+            .unwrap_or(0);
+        (idx, &self.clients[idx])
+    }
+
+    /// Records the outcome and latency of a request sent through the client at `idx`.
+    ///
+    /// `success` should only be false for failures of the client itself (see
+    /// [`is_client_failure`](super::client::is_client_failure)); consecutive ones
+    /// quarantine it.
+    pub fn record(&self, idx: usize, success: bool, latency: Duration) {
+        let pooled = &self.clients[idx];
+        let mut health = pooled.health.lock().unwrap();
+        health.total_latency += latency;
+
+        if success {
+            health.successes += 1;
+            health.consecutive_failures = 0;
+            if health.quarantined_until.take().is_some() {
+                info!(client = %pooled.label, "Client recovered, back in rotation");
+            }
+            return;
+        }
+
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= self.health_policy.max_consecutive_failures {
+            let cooldown = self.health_policy.cooldown;
+            if health.quarantined_until.is_none() {
+                warn!(
+                    client = %pooled.label,
+                    consecutive_failures = health.consecutive_failures,
+                    cooldown = ?cooldown,
+                    "Quarantining client"
+                );
+            }
+            health.quarantined_until = Some(Instant::now() + cooldown);
+        }
+    }
+
+    /// Statistics of every client in the pool.
+    pub fn health(&self) -> Vec<ProxyHealth> {
+        self.clients.iter().map(PooledClient::snapshot).collect()
+    }
+
+    /// Logs one line per client with its request statistics.
+    pub(crate) fn log_health(&self) {
+        for health in self.health() {
+            info!(
+                client = %health.label,
+                successes = health.successes,
+                failures = health.failures,
+                avg_latency_ms = health.avg_latency.as_millis() as u64,
+                quarantined = health.quarantined,
+                "Client health"
+            );
+        }
+    }
+
+    /// Smooth weighted round-robin: every client gains its score, the leader is picked
+    /// and pays back the total, which spreads picks in proportion to the scores.
+    fn next_weighted(&self) -> usize {
+        let scores: Vec<f64> = self.clients.iter().map(PooledClient::score).collect();
+        let total: f64 = scores.iter().sum();
+
+        let mut current = self.current_weights.lock().unwrap();
+        let mut best = 0;
+        for (idx, score) in scores.iter().enumerate() {
+            current[idx] += score;
+            if current[idx] > current[best] {
+                best = idx;
+            }
+        }
+        current[best] -= total;
+        best
+    }
+}
+
+/// Identifies a proxy in logs without its credentials.
+pub(crate) fn proxy_label(proxy: &str) -> String {
+    proxy.rsplit('@').next().unwrap_or(proxy).to_string()
+}
@@ -1,5 +1,8 @@
 use anyhow::Result;
 use tokio::fs;
+use tracing::{info, warn};
+
+use crate::core::Checkpoint;
 
 /// Loads all symbols from the CSV file into memory.
 /// Returns a vector of symbol strings (first column of the CSV).
@@ -28,3 +31,34 @@ pub async fn load_symbols(path: String) -> Result<Vec<String>> {
 
     Ok(symbols)
 }
+
+/// Loads proxies from a file with one `user:pass@host:port` entry per line.
+/// Blank lines and lines starting with `#` are ignored.
+pub async fn load_proxies(path: String) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path).await?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Index of the first symbol after the last completed one recorded in the checkpoint.
+///
+/// Starts from the beginning when there is no checkpoint or the symbol is no longer listed.
+pub(crate) async fn resume_index(symbols: &[String], checkpoint: &Checkpoint) -> Result<usize> {
+    let Some(last) = checkpoint.load().await? else {
+        return Ok(0);
+    };
+    match symbols.iter().position(|symbol| *symbol == last) {
+        Some(index) => {
+            info!(job = checkpoint.job(), last = %last, "Resuming symbols from checkpoint");
+            Ok(index + 1)
+        }
+        None => {
+            warn!(job = checkpoint.job(), last = %last, "Checkpoint symbol not found, starting over");
+            Ok(0)
+        }
+    }
+}
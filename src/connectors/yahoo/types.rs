@@ -1,43 +1,74 @@
 use crate::core::Message;
-use chrono::NaiveDateTime;
-use serde::Deserialize;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
 use yfinance_rs::fundamentals::{
     BalanceSheetRow as YBalanceSheetRow, CashflowRow as YCashflowRow,
     IncomeStatementRow as YIncomeStatementRow,
 };
+/// Reporting period of a financial statement.
+///
+/// Yahoo only publishes quarterly and annual statements; `Ttm` rows carry the quarter
+/// that ends the trailing twelve months and are rolled up by the storage handler.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFrequency {
+    #[default]
+    Quarterly,
+    Annual,
+    Ttm,
+}
+
+impl StatementFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementFrequency::Quarterly => "quarterly",
+            StatementFrequency::Annual => "annual",
+            StatementFrequency::Ttm => "ttm",
+        }
+    }
+}
+
 /// Wraps an IncomeStatementRow with a symbol field.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct IncomeStatementRow {
     pub symbol: String,
+    #[serde(default)]
+    pub frequency: StatementFrequency,
     #[serde(flatten)]
     pub inner: YIncomeStatementRow,
 }
 
 /// Wraps a BalanceSheetRow with a symbol field.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BalanceSheetRow {
     pub symbol: String,
+    #[serde(default)]
+    pub frequency: StatementFrequency,
     #[serde(flatten)]
     pub inner: YBalanceSheetRow,
 }
 
 /// Wraps a CashflowRow with a symbol field.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CashflowRow {
     pub symbol: String,
+    #[serde(default)]
+    pub frequency: StatementFrequency,
     #[serde(flatten)]
     pub inner: YCashflowRow,
 }
 
 /// Wraps a Calendar with a symbol field.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CalendarEntry {
     pub symbol: String,
     pub date_type: CalendarDateType,
     pub date: NaiveDateTime,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum CalendarDateType {
     #[serde(rename = "earnings")]
     Earnings,
@@ -47,12 +78,121 @@ pub enum CalendarDateType {
     DividendPayment,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+/// Length of a price bar.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BarInterval {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl BarInterval {
+    /// Yahoo's name for the interval, e.g. `1d`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarInterval::Hour => "1h",
+            BarInterval::Day => "1d",
+            BarInterval::Week => "1wk",
+            BarInterval::Month => "1mo",
+        }
+    }
+}
+
+/// Unadjusted OHLCV bar; the bar opens at `timestamp` (UTC).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PriceBar {
+    pub symbol: String,
+    pub interval: BarInterval,
+    pub timestamp: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<i64>,
+}
+
+impl PriceBar {
+    /// Bar from a fetched candle, or `None` when a price has no `f64` value. A volume
+    /// beyond `i64` is dropped rather than wrapped.
+    pub fn new(
+        symbol: &str,
+        interval: BarInterval,
+        timestamp: DateTime<Utc>,
+        [open, high, low, close]: [Decimal; 4],
+        volume: Option<u64>,
+    ) -> Option<Self> {
+        Some(Self {
+            symbol: symbol.to_string(),
+            interval,
+            timestamp: timestamp.naive_utc(),
+            open: open.to_f64()?,
+            high: high.to_f64()?,
+            low: low.to_f64()?,
+            close: close.to_f64()?,
+            volume: volume.and_then(|volume| i64::try_from(volume).ok()),
+        })
+    }
+}
+
+/// Cash dividend per share, keyed by its ex-dividend date (UTC).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Dividend {
+    pub symbol: String,
+    pub ex_date: NaiveDateTime,
+    pub amount: f64,
+}
+
+impl Dividend {
+    /// Dividend from a fetched action, or `None` when the amount has no `f64` value.
+    pub fn new(symbol: &str, ex_date: DateTime<Utc>, amount: Decimal) -> Option<Self> {
+        Some(Self {
+            symbol: symbol.to_string(),
+            ex_date: ex_date.naive_utc(),
+            amount: amount.to_f64()?,
+        })
+    }
+}
+
+/// Stock split of `numerator` new shares for every `denominator` old ones.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Split {
+    pub symbol: String,
+    pub date: NaiveDateTime,
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Split {
+    /// Split from a fetched action, or `None` when either side of the ratio is zero or
+    /// too large to store.
+    pub fn new(
+        symbol: &str,
+        date: DateTime<Utc>,
+        numerator: u32,
+        denominator: u32,
+    ) -> Option<Self> {
+        let valid = |n: u32| n > 0 && i32::try_from(n).is_ok();
+        (valid(numerator) && valid(denominator)).then(|| Self {
+            symbol: symbol.to_string(),
+            date: date.naive_utc(),
+            numerator,
+            denominator,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum YahooMessage {
     Calendar(CalendarEntry),
     IncomeStatement(IncomeStatementRow),
     BalanceSheet(BalanceSheetRow),
     Cashflow(CashflowRow),
+    PriceBar(PriceBar),
+    Dividend(Dividend),
+    Split(Split),
 }
 
 impl Message for YahooMessage {}
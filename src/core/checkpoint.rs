@@ -0,0 +1,170 @@
+//! Resume positions of long-running bulk loads and the stores that persist them.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+type AsyncResult<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Persists the resume position of long-running bulk loads, keyed by job name.
+pub trait CheckpointStore: Send + Sync + 'static {
+    /// Returns the last saved position of a job, if any.
+    fn load<'a>(&'a self, job: &'a str) -> AsyncResult<'a, Option<String>>;
+
+    /// Records the position a job has reached.
+    fn save<'a>(&'a self, job: &'a str, position: &'a str) -> AsyncResult<'a, ()>;
+
+    /// Discards the position so the job starts from the beginning.
+    fn clear<'a>(&'a self, job: &'a str) -> AsyncResult<'a, ()>;
+}
+
+/// Checkpoint of a single job in a shared store.
+#[derive(Clone)]
+pub struct Checkpoint {
+    store: Arc<dyn CheckpointStore>,
+    job: String,
+}
+
+impl Checkpoint {
+    pub fn new(store: Arc<dyn CheckpointStore>, job: &str) -> Self {
+        Self {
+            store,
+            job: job.to_string(),
+        }
+    }
+
+    pub fn job(&self) -> &str {
+        &self.job
+    }
+
+    pub async fn load(&self) -> Result<Option<String>> {
+        self.store.load(&self.job).await
+    }
+
+    pub async fn save(&self, position: &str) -> Result<()> {
+        self.store.save(&self.job, position).await
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        self.store.clear(&self.job).await
+    }
+}
+
+/// Saves positions of a [`Checkpoint`] only once every batch sent up to them must have
+/// reached the sink.
+///
+/// A source sending on a channel cannot see when the sink has persisted a batch, only
+/// that the channel accepted it. With `in_flight` covering the batches that can sit in
+/// the channel or in the sink after a send returns, a position is saved once that many
+/// more batches were sent. A resumed run re-fetches at most those batches, so sinks
+/// should write idempotently.
+///
+/// Delivery is only at-least-once when the processor stops on the first batch the sink
+/// fails (`max_sink_errors(1)`); one that logs sink errors and carries on lets the
+/// checkpoint move past batches that were never written. Overflow policies other than
+/// `Block` buffer beyond the channel and do not carry the guarantee either.
+pub struct TrailingCheckpoint {
+    checkpoint: Checkpoint,
+    in_flight: usize,
+    /// Positions not yet saved, with the number of batches sent when they were reached
+    pending: VecDeque<(usize, String)>,
+}
+
+impl TrailingCheckpoint {
+    pub fn new(checkpoint: Checkpoint, in_flight: usize) -> Self {
+        Self {
+            checkpoint,
+            in_flight,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Batches that may be unwritten after a send on a channel of `capacity` returns,
+    /// with `senders` tasks sending concurrently: the channel's contents, the batch
+    /// the sink is writing and the sends of other tasks not yet counted.
+    pub fn in_flight(capacity: usize, senders: usize) -> usize {
+        capacity + senders.max(1)
+    }
+
+    /// Records that everything up to `position` was sent once `sent` batches had been
+    /// sent in total, and saves the latest position that is now safe to resume after.
+    pub async fn advance(&mut self, position: &str, sent: usize) -> Result<()> {
+        self.pending.push_back((sent, position.to_string()));
+        let mut safe = None;
+        while let Some((reached, _)) = self.pending.front()
+            && sent - reached >= self.in_flight
+        {
+            safe = self.pending.pop_front();
+        }
+        match safe {
+            Some((_, position)) => self.checkpoint.save(&position).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Discards the position once the whole job has been sent.
+    pub async fn clear(&mut self) -> Result<()> {
+        self.pending.clear();
+        self.checkpoint.clear().await
+    }
+}
+
+/// Stores each job's position in `<dir>/<job>.checkpoint`.
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, job: &str) -> PathBuf {
+        self.dir.join(format!("{job}.checkpoint"))
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load<'a>(&'a self, job: &'a str) -> AsyncResult<'a, Option<String>> {
+        Box::pin(async move {
+            match tokio::fs::read_to_string(self.path(job)).await {
+                Ok(position) => Ok(Some(position)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err).with_context(|| format!("Failed to read checkpoint {job}")),
+            }
+        })
+    }
+
+    fn save<'a>(&'a self, job: &'a str, position: &'a str) -> AsyncResult<'a, ()> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.dir)
+                .await
+                .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+
+            // Write then rename so a crash never leaves a truncated checkpoint
+            let path = self.path(job);
+            let tmp = path.with_extension("checkpoint.tmp");
+            tokio::fs::write(&tmp, position)
+                .await
+                .with_context(|| format!("Failed to write checkpoint {job}"))?;
+            tokio::fs::rename(&tmp, &path)
+                .await
+                .with_context(|| format!("Failed to write checkpoint {job}"))?;
+            Ok(())
+        })
+    }
+
+    fn clear<'a>(&'a self, job: &'a str) -> AsyncResult<'a, ()> {
+        Box::pin(async move {
+            match tokio::fs::remove_file(self.path(job)).await {
+                Ok(()) => Ok(()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(err).with_context(|| format!("Failed to clear checkpoint {job}")),
+            }
+        })
+    }
+}
@@ -1,4 +1,6 @@
 //! Core messaging traits and type aliases shared across Tickflow components.
+mod checkpoint;
 mod traits;
 
-pub use traits::{Message, MessageBatch, MessageSink, MessageSource};
+pub use checkpoint::{Checkpoint, CheckpointStore, FileCheckpointStore, TrailingCheckpoint};
+pub use traits::{Message, MessageBatch, MessageOutcome, MessageSink, MessageSource, PartialWrite};
@@ -7,6 +7,47 @@ pub trait Message: Send + Sync + Clone + 'static {}
 /// Batch of messages processed together.
 pub type MessageBatch<M> = Vec<M>;
 
+/// Outcome of a single message handed to a sink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageOutcome {
+    Ok,
+    /// The message was already stored and was left unchanged
+    Duplicate,
+    /// The sink did not store the message, with the reason
+    Rejected(String),
+    /// The sink deliberately left out an incomplete or malformed message; retrying it
+    /// would not help
+    Skipped(String),
+}
+
+impl MessageOutcome {
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, MessageOutcome::Rejected(_))
+    }
+}
+
+/// Error of a sink that reached its store but failed to write some rows, as opposed to
+/// a sink that is down; processors keep treating the sink as reachable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialWrite {
+    pub failed: usize,
+    pub total: usize,
+    /// Reason of the first failed row
+    pub first: String,
+}
+
+impl std::fmt::Display for PartialWrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} rows failed to insert, first: {}",
+            self.failed, self.total, self.first
+        )
+    }
+}
+
+impl std::error::Error for PartialWrite {}
+
 /// Trait for sinks that handle batches of messages asynchronously.
 pub trait MessageSink<M: Message>: Send + Sync + 'static {
     fn name(&self) -> &'static str;
@@ -15,6 +56,26 @@ pub trait MessageSink<M: Message>: Send + Sync + 'static {
         &'a self,
         batch: MessageBatch<M>,
     ) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>;
+
+    /// Handles a batch and reports one outcome per message, in batch order.
+    ///
+    /// An error means the whole batch failed. The default adapter reports every message
+    /// as ok once `handle_batch` succeeds.
+    fn handle_batch_outcomes<'a>(
+        &'a self,
+        batch: MessageBatch<M>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Vec<MessageOutcome>>> + Send + 'a>> {
+        Box::pin(async move {
+            let len = batch.len();
+            self.handle_batch(batch).await?;
+            Ok(vec![MessageOutcome::Ok; len])
+        })
+    }
+
+    /// Flushes whatever the sink still buffers once no more batches will arrive.
+    fn close<'a>(&'a self) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>> {
+        Box::pin(async { Ok(()) })
+    }
 }
 
 /// Trait for sources that produce batches of messages asynchronously.
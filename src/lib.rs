@@ -4,9 +4,15 @@ pub mod config;
 pub mod core;
 pub mod pipeline;
 pub mod prelude;
+pub mod secret;
 
-#[cfg(any(feature = "alpaca", feature = "yahoo", feature = "polymarket"))]
+#[cfg(any(
+    feature = "alpaca",
+    feature = "yahoo",
+    feature = "polymarket",
+    feature = "websocket"
+))]
 pub mod connectors;
 
-#[cfg(feature = "postgres")]
+#[cfg(any(feature = "postgres", feature = "file"))]
 pub mod storage;
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 
 use crate::core::{Message, MessageSink, MessageSource};
 
-use super::{SPSCDataFeed, SPSCDataFeedHandles};
+use super::{MessageProcessor, OverflowPolicy, PipelineStatus, SPSCDataFeed, SPSCDataFeedHandles};
 
 /// Fluent builder for constructing and launching an `SPSCDataFeed`.
 ///
@@ -17,9 +17,11 @@ where
     Sink: MessageSink<M>,
 {
     source: Src,
-    sink: Sink,
+    processor: MessageProcessor<M>,
     channel_capacity: usize,
-    _marker: PhantomData<M>,
+    status: Option<PipelineStatus>,
+    overflow: OverflowPolicy<M>,
+    _marker: PhantomData<Sink>,
 }
 
 impl<M, Src, Sink> TickflowBuilder<M, Src, Sink>
@@ -32,8 +34,10 @@ where
     pub fn new(source: Src, sink: Sink) -> Self {
         Self {
             source,
-            sink,
+            processor: MessageProcessor::new(sink),
             channel_capacity: 1_000,
+            status: None,
+            overflow: OverflowPolicy::Block,
             _marker: PhantomData,
         }
     }
@@ -44,15 +48,66 @@ where
         self
     }
 
+    /// Routes messages the sink still rejects after all retries to `sink`.
+    pub fn dead_letter<D>(mut self, sink: D) -> Self
+    where
+        D: MessageSink<M>,
+    {
+        self.processor = self.processor.dead_letter(sink);
+        self
+    }
+
+    /// Resubmits messages the sink rejects up to `retries` times.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.processor = self.processor.retries(retries);
+        self
+    }
+
+    /// Stops the feed with an error after `errors` consecutive batches failed in the
+    /// sink, e.g. to let a supervisor restart it; sink errors are only logged otherwise.
+    pub fn max_sink_errors(mut self, errors: u32) -> Self {
+        self.processor = self.processor.max_sink_errors(errors);
+        self
+    }
+
+    /// Keeps `status` up to date with the source, channel and sink of the feed.
+    pub fn status(mut self, status: PipelineStatus) -> Self {
+        self.processor = self.processor.status(status.clone());
+        self.status = Some(status);
+        self
+    }
+
+    /// Sets what happens to new batches while the channel is full.
+    ///
+    /// The default, [`OverflowPolicy::Block`], makes the source wait. The other policies
+    /// keep the source reading and drop, conflate or spill batches instead; drops are
+    /// logged and counted in the pipeline status.
+    pub fn overflow(mut self, policy: OverflowPolicy<M>) -> Self {
+        self.overflow = policy;
+        self
+    }
+
     /// Builds an `SPSCDataFeed` without starting any asynchronous tasks.
     pub fn build(self) -> SPSCDataFeed<M, Src> {
         let Self {
             source,
-            sink,
+            processor,
             channel_capacity,
+            status,
+            overflow,
             ..
         } = self;
-        SPSCDataFeed::new(source, sink, channel_capacity)
+        let feed =
+            SPSCDataFeed::with_processor(source, processor, channel_capacity).overflow(overflow);
+        match status {
+            Some(status) => feed.status(status),
+            None => feed,
+        }
+    }
+
+    /// Builds and runs the data feed to completion, returning the first error.
+    pub async fn run(self) -> anyhow::Result<()> {
+        self.build().run().await
     }
 
     /// Builds and starts the data feed, returning the spawned task handles.
@@ -0,0 +1,127 @@
+//! US equity market calendar (NYSE/Nasdaq) for trading-day aware schedules.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::America::New_York;
+
+/// Regular and early closing times, in New York time.
+const REGULAR_CLOSE: (u32, u32) = (16, 0);
+const EARLY_CLOSE: (u32, u32) = (13, 0);
+
+/// Trading days and closing times of the US equity market.
+///
+/// Holidays follow the NYSE rules: fixed-date holidays falling on a Saturday are
+/// observed the Friday before (except New Year's Day) and on a Sunday the Monday after.
+pub struct UsEquityCalendar;
+
+impl UsEquityCalendar {
+    /// Whether the market opens on `date`.
+    pub fn is_trading_day(date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !Self::is_holiday(date)
+    }
+
+    /// Closing time on `date`, or `None` when the market is closed all day.
+    pub fn close(date: NaiveDate) -> Option<DateTime<Utc>> {
+        if !Self::is_trading_day(date) {
+            return None;
+        }
+        let (hour, minute) = if Self::is_early_close(date) {
+            EARLY_CLOSE
+        } else {
+            REGULAR_CLOSE
+        };
+        let local = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?);
+        New_York
+            .from_local_datetime(&local)
+            .single()
+            .map(|close| close.with_timezone(&Utc))
+    }
+
+    /// Whether `date` is a full-day market holiday.
+    pub fn is_holiday(date: NaiveDate) -> bool {
+        let year = date.year();
+        let mut holidays = vec![
+            nth_weekday(year, 1, Weekday::Mon, 3),
+            nth_weekday(year, 2, Weekday::Mon, 3),
+            easter(year) - Duration::days(2),
+            last_weekday(year, 5, Weekday::Mon),
+            observed(ymd(year, 7, 4)),
+            nth_weekday(year, 9, Weekday::Mon, 1),
+            nth_weekday(year, 11, Weekday::Thu, 4),
+            observed(ymd(year, 12, 25)),
+        ];
+        // A Saturday New Year's Day is not observed on the Friday before
+        let new_year = ymd(year, 1, 1);
+        if new_year.weekday() != Weekday::Sat {
+            holidays.push(observed(new_year));
+        }
+        if year >= 2022 {
+            holidays.push(observed(ymd(year, 6, 19)));
+        }
+        holidays.contains(&date)
+    }
+
+    /// Whether the market closes at 1pm: July 3rd, the day after Thanksgiving and
+    /// Christmas Eve, when those are regular weekdays before the holiday.
+    pub fn is_early_close(date: NaiveDate) -> bool {
+        let year = date.year();
+        let before_holiday = |day: NaiveDate| {
+            day == date
+                && matches!(
+                    day.weekday(),
+                    Weekday::Mon | Weekday::Tue | Weekday::Wed | Weekday::Thu
+                )
+        };
+        before_holiday(ymd(year, 7, 3))
+            || before_holiday(ymd(year, 12, 24))
+            || date == nth_weekday(year, 11, Weekday::Thu, 4) + Duration::days(1)
+    }
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("valid calendar date")
+}
+
+/// Moves a Saturday holiday to Friday and a Sunday holiday to Monday.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8).expect("valid weekday")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    let next_month = if month == 12 {
+        ymd(year + 1, 1, 1)
+    } else {
+        ymd(year, month + 1, 1)
+    };
+    let mut date = next_month - Duration::days(1);
+    while date.weekday() != weekday {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+/// Easter Sunday (anonymous Gregorian algorithm).
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    ymd(year, month as u32, day as u32)
+}
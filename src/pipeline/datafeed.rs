@@ -6,7 +6,8 @@ use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tracing::error;
 
-use super::{MessageProcessor, TickflowBuilder};
+use super::overflow::relay;
+use super::{MessageProcessor, OverflowPolicy, PipelineStatus, TickflowBuilder};
 
 /// Connects a `MessageSource` to a `MessageProcessor` via a bounded Tokio channel.
 pub struct SPSCDataFeed<M, Src>
@@ -17,6 +18,8 @@ where
     source: Src,
     processor: MessageProcessor<M>,
     channel_capacity: usize,
+    status: Option<PipelineStatus>,
+    overflow: OverflowPolicy<M>,
 }
 
 /// Task handles returned when an `SPSCDataFeed` is started.
@@ -43,27 +46,77 @@ where
     where
         Sink: MessageSink<M>,
     {
+        Self::with_processor(source, MessageProcessor::new(sink), channel_capacity)
+    }
+
+    /// Creates a feed around an already configured processor.
+    pub fn with_processor(
+        source: Src,
+        processor: MessageProcessor<M>,
+        channel_capacity: usize,
+    ) -> Self {
         Self {
             source,
-            processor: MessageProcessor::new(sink),
+            processor,
             channel_capacity,
+            status: None,
+            overflow: OverflowPolicy::Block,
         }
     }
 
+    /// Reports whether the source runs, source errors and the channel fill level to
+    /// `status`. Pass the same status to the processor to track the sink as well.
+    pub fn status(mut self, status: PipelineStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Sets what happens to new batches while the channel is full; blocks by default.
+    pub fn overflow(mut self, policy: OverflowPolicy<M>) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Runs source and processor on the current task until both finish.
+    ///
+    /// Unlike `start`, errors are returned rather than logged, the processor's first:
+    /// a processor that gives up on its sink also stops the source. A failed source
+    /// still lets the processor drain the batches already queued.
+    pub async fn run(self) -> Result<()> {
+        let (tx, rx) = mpsc::channel::<MessageBatch<M>>(self.channel_capacity);
+
+        let source = run_source(self.source, tx, self.status.clone());
+        let consumer = consume(
+            self.processor,
+            self.overflow,
+            self.channel_capacity,
+            self.status,
+            rx,
+        );
+        let (source, consumer) = tokio::join!(source, consumer);
+        consumer.and(source)
+    }
+
     /// Spawns source and processor tasks and returns their join handles.
     pub async fn start(self) -> Result<SPSCDataFeedHandles> {
         let (tx, rx) = mpsc::channel::<MessageBatch<M>>(self.channel_capacity);
 
-        let mut source = self.source;
+        let source = run_source(self.source, tx, self.status.clone());
         let source_handle = tokio::spawn(async move {
-            if let Err(err) = source.run(tx).await {
+            if let Err(err) = source.await {
                 error!("Source task failed: {err}");
             }
         });
 
-        let processor = self.processor;
+        let consumer = consume(
+            self.processor,
+            self.overflow,
+            self.channel_capacity,
+            self.status,
+            rx,
+        );
         let processor_handle = tokio::spawn(async move {
-            if let Err(err) = processor.process_messages(rx).await {
+            if let Err(err) = consumer.await {
                 error!("Processor task failed: {err}");
             }
         });
@@ -74,3 +127,45 @@ where
         })
     }
 }
+
+/// Runs `source`, keeping `status` up to date while it runs.
+async fn run_source<M, Src>(
+    mut source: Src,
+    tx: mpsc::Sender<MessageBatch<M>>,
+    status: Option<PipelineStatus>,
+) -> Result<()>
+where
+    M: Message,
+    Src: MessageSource<M>,
+{
+    let Some(status) = status else {
+        return source.run(tx).await;
+    };
+    status.watch_channel(&tx);
+    status.set_running(true);
+    let result = source.run(tx).await;
+    status.set_running(false);
+    if let Err(err) = &result {
+        status.record_error(&format!("{err:#}"));
+    }
+    result
+}
+
+/// Runs the processor on `rx`, behind a relay stage unless the policy blocks.
+async fn consume<M: Message>(
+    processor: MessageProcessor<M>,
+    overflow: OverflowPolicy<M>,
+    capacity: usize,
+    status: Option<PipelineStatus>,
+    rx: mpsc::Receiver<MessageBatch<M>>,
+) -> Result<()> {
+    if let OverflowPolicy::Block = overflow {
+        return processor.process_messages(rx).await;
+    }
+    let (tx, relayed) = mpsc::channel(1);
+    let (relay, processor) = tokio::join!(
+        relay(overflow, capacity, status, rx, tx),
+        processor.process_messages(relayed)
+    );
+    processor.and(relay)
+}
@@ -0,0 +1,136 @@
+//! Embedded HTTP server exposing liveness, readiness and pipeline status.
+//!
+//! | path | response |
+//! |------|----------|
+//! | `/healthz` | `200` while the process is alive |
+//! | `/readyz` | `200` when every pipeline is ready, otherwise `503` listing the problems |
+//! | `/status` | per-pipeline JSON built from [`PipelineSnapshot`] |
+
+use std::net::SocketAddr;
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tracing::info;
+
+use super::{PipelineSnapshot, StatusRegistry};
+
+/// Serves the health endpoints for the pipelines in a [`StatusRegistry`].
+pub struct HealthServer {
+    listener: TcpListener,
+    registry: StatusRegistry,
+}
+
+impl HealthServer {
+    /// Binds the listening socket; port 0 picks a free port.
+    pub async fn bind(addr: impl ToSocketAddrs, registry: StatusRegistry) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .context("failed to bind the health server")?;
+        Ok(Self { listener, registry })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until the task is dropped.
+    pub async fn serve(self) -> Result<()> {
+        info!(addr = %self.local_addr()?, "Health server listening");
+        axum::serve(self.listener, router(self.registry)).await?;
+        Ok(())
+    }
+}
+
+/// Routes of the health server, for embedding in another axum application.
+pub fn router(registry: StatusRegistry) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { "ok\n" }))
+        .route("/readyz", get(readyz))
+        .route("/status", get(status))
+        .with_state(registry)
+}
+
+async fn readyz(State(registry): State<StatusRegistry>) -> (StatusCode, String) {
+    let problems: Vec<String> = registry
+        .snapshots()
+        .iter()
+        .flat_map(PipelineSnapshot::problems)
+        .collect();
+    if problems.is_empty() {
+        (StatusCode::OK, "ready\n".to_string())
+    } else {
+        let body = problems
+            .iter()
+            .map(|problem| format!("{problem}\n"))
+            .collect();
+        (StatusCode::SERVICE_UNAVAILABLE, body)
+    }
+}
+
+async fn status(State(registry): State<StatusRegistry>) -> Json<StatusReport> {
+    let pipelines = registry
+        .snapshots()
+        .into_iter()
+        .map(PipelineReport::from)
+        .collect();
+    Json(StatusReport { pipelines })
+}
+
+#[derive(Serialize)]
+struct StatusReport {
+    pipelines: Vec<PipelineReport>,
+}
+
+#[derive(Serialize)]
+struct PipelineReport {
+    name: String,
+    ready: bool,
+    problems: Vec<String>,
+    running: bool,
+    finished: bool,
+    connected: Option<bool>,
+    last_run_failed: bool,
+    sink_reachable: Option<bool>,
+    last_message_at: Option<DateTime<Utc>>,
+    messages: u64,
+    batches: u64,
+    rejected: u64,
+    dropped: u64,
+    spilled: u64,
+    errors: u64,
+    last_error: Option<String>,
+    queued: usize,
+    capacity: usize,
+}
+
+impl From<PipelineSnapshot> for PipelineReport {
+    fn from(snapshot: PipelineSnapshot) -> Self {
+        let problems = snapshot.problems();
+        Self {
+            ready: problems.is_empty(),
+            problems,
+            name: snapshot.name,
+            running: snapshot.running,
+            finished: snapshot.finished,
+            connected: snapshot.connected,
+            last_run_failed: snapshot.last_run_failed,
+            sink_reachable: snapshot.sink_reachable,
+            last_message_at: snapshot.last_message_at.map(DateTime::from),
+            messages: snapshot.messages,
+            batches: snapshot.batches,
+            rejected: snapshot.rejected,
+            dropped: snapshot.dropped,
+            spilled: snapshot.spilled,
+            errors: snapshot.errors,
+            last_error: snapshot.last_error,
+            queued: snapshot.queued,
+            capacity: snapshot.capacity,
+        }
+    }
+}
@@ -2,8 +2,24 @@
 
 pub use self::builder::TickflowBuilder;
 pub use self::datafeed::{SPSCDataFeed, SPSCDataFeedHandles};
+#[cfg(feature = "health")]
+pub use self::health::HealthServer;
+pub use self::overflow::{ConflationKey, OverflowPolicy};
 pub use self::processor::MessageProcessor;
+#[cfg(feature = "scheduler")]
+pub use self::scheduler::{Schedule, ScheduledSource};
+pub use self::status::{PipelineSnapshot, PipelineStatus, StatusRegistry};
+pub use self::supervisor::{Restart, RestartPolicy, Supervisor};
 
 pub mod builder;
+#[cfg(feature = "scheduler")]
+pub mod calendar;
 pub mod datafeed;
+#[cfg(feature = "health")]
+pub mod health;
+pub mod overflow;
 pub mod processor;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+pub mod status;
+pub mod supervisor;
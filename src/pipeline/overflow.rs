@@ -0,0 +1,459 @@
+//! Overflow policies applied when a pipeline's channel is full.
+//!
+//! With any policy but [`OverflowPolicy::Block`], a relay stage sits between source and
+//! processor. It keeps reading from the source so websocket reads never stall, and
+//! buffers up to the channel capacity in batches before the policy applies.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tracing::{info, warn};
+
+use crate::core::{Message, MessageBatch};
+
+use super::PipelineStatus;
+
+/// Extracts the key messages are conflated by; messages without a key are always kept.
+pub type ConflationKey<M> = Arc<dyn Fn(&M) -> Option<String> + Send + Sync>;
+
+/// What the pipeline does with new batches while its channel is full.
+pub enum OverflowPolicy<M> {
+    /// Wait for room, stalling the source (the default)
+    Block,
+    /// Discard the oldest buffered batch to make room
+    DropOldest,
+    /// Discard the incoming batch
+    DropNewest,
+    /// Keep only the latest buffered message per key, e.g. the latest quote per symbol
+    ConflateLatest(ConflationKey<M>),
+    /// Write batches that do not fit to a file and replay them in order
+    #[cfg(feature = "file")]
+    SpillToDisk(spill::SpillConfig<M>),
+}
+
+impl<M> OverflowPolicy<M> {
+    /// Conflates buffered messages by the key `key` returns.
+    pub fn conflate_latest<F>(key: F) -> Self
+    where
+        F: Fn(&M) -> Option<String> + Send + Sync + 'static,
+    {
+        OverflowPolicy::ConflateLatest(Arc::new(key))
+    }
+
+    /// Spills batches that do not fit to `path` as JSON lines.
+    #[cfg(feature = "file")]
+    pub fn spill_to_disk(path: impl Into<std::path::PathBuf>) -> Self
+    where
+        M: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        OverflowPolicy::SpillToDisk(spill::SpillConfig::new(path.into()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverflowPolicy::Block => "block",
+            OverflowPolicy::DropOldest => "drop_oldest",
+            OverflowPolicy::DropNewest => "drop_newest",
+            OverflowPolicy::ConflateLatest(_) => "conflate_latest",
+            #[cfg(feature = "file")]
+            OverflowPolicy::SpillToDisk(_) => "spill_to_disk",
+        }
+    }
+}
+
+/// Forwards batches from `input` to `output`, applying `policy` once `capacity` batches
+/// are buffered. Returns when the source has finished and everything was forwarded, or
+/// when the processor has gone away.
+pub(crate) async fn relay<M: Message>(
+    policy: OverflowPolicy<M>,
+    capacity: usize,
+    status: Option<PipelineStatus>,
+    mut input: Receiver<MessageBatch<M>>,
+    output: Sender<MessageBatch<M>>,
+) -> Result<()> {
+    let mut relay = Relay {
+        policy,
+        capacity: capacity.max(1),
+        status,
+        buffer: VecDeque::new(),
+        #[cfg(feature = "file")]
+        spill: None,
+        dropped: 0,
+        spilled: 0,
+        overflowing: false,
+    };
+    #[cfg(feature = "file")]
+    relay.resume_spill().await?;
+    relay.report_buffer();
+    let mut input_open = true;
+
+    loop {
+        if relay.buffer.is_empty() {
+            relay.unspill().await?;
+            relay.report_buffer();
+        }
+        if relay.buffer.is_empty() && !input_open {
+            break;
+        }
+        let accepting = input_open && !(relay.is_full() && relay.blocks());
+
+        tokio::select! {
+            batch = input.recv(), if accepting => match batch {
+                Some(batch) => {
+                    relay.push(batch).await?;
+                    relay.report_buffer();
+                }
+                None => input_open = false,
+            },
+            permit = output.reserve(), if !relay.buffer.is_empty() => match permit {
+                Ok(permit) => {
+                    if let Some(batch) = relay.buffer.pop_front() {
+                        permit.send(batch);
+                    }
+                    relay.report_buffer();
+                    relay.check_cleared();
+                }
+                Err(_) => break,
+            },
+            // The processor gave up, so stop reading and let the source fail on send
+            () = output.closed() => break,
+        }
+    }
+
+    relay.finish().await
+}
+
+struct Relay<M> {
+    policy: OverflowPolicy<M>,
+    capacity: usize,
+    status: Option<PipelineStatus>,
+    buffer: VecDeque<MessageBatch<M>>,
+    #[cfg(feature = "file")]
+    spill: Option<spill::SpillFile<M>>,
+    dropped: u64,
+    spilled: u64,
+    overflowing: bool,
+}
+
+impl<M: Message> Relay<M> {
+    fn is_full(&self) -> bool {
+        self.buffer.len() >= self.capacity
+    }
+
+    fn blocks(&self) -> bool {
+        matches!(self.policy, OverflowPolicy::Block)
+    }
+
+    fn spill_pending(&self) -> bool {
+        #[cfg(feature = "file")]
+        if let Some(spill) = &self.spill {
+            return spill.pending() > 0;
+        }
+        false
+    }
+
+    async fn push(&mut self, batch: MessageBatch<M>) -> Result<()> {
+        // Batches keep their order: once spilling, new batches queue behind the file
+        if !self.is_full() && !self.spill_pending() {
+            self.buffer.push_back(batch);
+            return Ok(());
+        }
+
+        match &self.policy {
+            OverflowPolicy::Block => self.buffer.push_back(batch),
+            OverflowPolicy::DropOldest => {
+                let dropped = self.buffer.pop_front().map_or(0, |old| old.len());
+                self.buffer.push_back(batch);
+                self.record_dropped(dropped);
+            }
+            OverflowPolicy::DropNewest => self.record_dropped(batch.len()),
+            OverflowPolicy::ConflateLatest(key) => {
+                let key = key.clone();
+                let buffered = self.buffer.drain(..).flatten().chain(batch);
+                let (conflated, replaced) = conflate(buffered, key.as_ref());
+                self.buffer.push_back(conflated);
+                self.record_dropped(replaced);
+            }
+            #[cfg(feature = "file")]
+            OverflowPolicy::SpillToDisk(config) => {
+                let messages = batch.len();
+                let spill = match &mut self.spill {
+                    Some(spill) => spill,
+                    None => self
+                        .spill
+                        .insert(spill::SpillFile::open(config.clone()).await?),
+                };
+                spill.push(&batch).await?;
+                self.spilled += messages as u64;
+                if let Some(status) = &self.status {
+                    status.record_spilled(messages);
+                }
+                self.start_overflow();
+            }
+        }
+        Ok(())
+    }
+
+    /// Reopens a spill file left behind by an earlier run, e.g. after a crash, so its
+    /// batches are replayed before any new ones.
+    #[cfg(feature = "file")]
+    async fn resume_spill(&mut self) -> Result<()> {
+        let OverflowPolicy::SpillToDisk(config) = &self.policy else {
+            return Ok(());
+        };
+        let Some(spill) = spill::SpillFile::resume(config.clone()).await? else {
+            return Ok(());
+        };
+        if spill.pending() > 0 {
+            warn!(
+                batches = spill.pending(),
+                "Replaying batches spilled by an earlier run"
+            );
+            self.start_overflow();
+        }
+        // Kept even when empty so the file is removed on finish
+        self.spill = Some(spill);
+        Ok(())
+    }
+
+    /// Reports the buffer fill level, which is what backs up while the processor lags;
+    /// the channel from the source is emptied as fast as it fills.
+    fn report_buffer(&self) {
+        if let Some(status) = &self.status {
+            status.record_buffered(self.buffer.len(), self.capacity);
+        }
+    }
+
+    /// Moves the oldest spilled batch back into the buffer.
+    async fn unspill(&mut self) -> Result<()> {
+        #[cfg(feature = "file")]
+        if let Some(spill) = &mut self.spill
+            && let Some(batch) = spill.pop().await?
+        {
+            self.buffer.push_back(batch);
+        }
+        Ok(())
+    }
+
+    fn record_dropped(&mut self, messages: usize) {
+        if messages == 0 {
+            return;
+        }
+        self.dropped += messages as u64;
+        if let Some(status) = &self.status {
+            status.record_dropped(messages);
+        }
+        self.start_overflow();
+    }
+
+    fn start_overflow(&mut self) {
+        if !self.overflowing {
+            self.overflowing = true;
+            warn!(
+                policy = self.policy.name(),
+                dropped = self.dropped,
+                spilled = self.spilled,
+                "Pipeline channel is full, applying overflow policy"
+            );
+        }
+    }
+
+    fn check_cleared(&mut self) {
+        if self.overflowing && self.buffer.is_empty() && !self.spill_pending() {
+            self.overflowing = false;
+            info!(
+                dropped = self.dropped,
+                spilled = self.spilled,
+                "Pipeline channel overflow cleared"
+            );
+        }
+    }
+
+    async fn finish(self) -> Result<()> {
+        if self.dropped > 0 {
+            warn!(
+                policy = self.policy.name(),
+                dropped = self.dropped,
+                "Messages were dropped while the pipeline channel was full"
+            );
+        }
+        #[cfg(feature = "file")]
+        if let Some(spill) = self.spill {
+            spill.close().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps the latest message per key in the slot of the key's first occurrence, and
+/// returns the conflated batch with the number of messages replaced.
+fn conflate<M>(
+    messages: impl Iterator<Item = M>,
+    key: &(dyn Fn(&M) -> Option<String> + Send + Sync),
+) -> (MessageBatch<M>, usize) {
+    let mut conflated = Vec::new();
+    let mut slots = HashMap::new();
+    let mut replaced = 0;
+    for message in messages {
+        match key(&message) {
+            Some(key) => match slots.get(&key) {
+                Some(&slot) => {
+                    conflated[slot] = message;
+                    replaced += 1;
+                }
+                None => {
+                    slots.insert(key, conflated.len());
+                    conflated.push(message);
+                }
+            },
+            None => conflated.push(message),
+        }
+    }
+    (conflated, replaced)
+}
+
+#[cfg(feature = "file")]
+mod spill {
+    use std::io::SeekFrom;
+    use std::path::PathBuf;
+
+    use anyhow::{Context, Result};
+    use tokio::fs::{self, File, OpenOptions};
+    use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+    use tracing::warn;
+
+    use crate::core::MessageBatch;
+
+    type Encode<M> = fn(&MessageBatch<M>) -> serde_json::Result<String>;
+    type Decode<M> = fn(&str) -> serde_json::Result<MessageBatch<M>>;
+
+    /// Where and how spilled batches are stored.
+    pub struct SpillConfig<M> {
+        path: PathBuf,
+        encode: Encode<M>,
+        decode: Decode<M>,
+    }
+
+    impl<M> SpillConfig<M> {
+        pub(super) fn new(path: PathBuf) -> Self
+        where
+            M: serde::Serialize + serde::de::DeserializeOwned,
+        {
+            Self {
+                path,
+                encode: |batch| serde_json::to_string(batch),
+                decode: |line| serde_json::from_str(line),
+            }
+        }
+    }
+
+    impl<M> Clone for SpillConfig<M> {
+        fn clone(&self) -> Self {
+            Self {
+                path: self.path.clone(),
+                encode: self.encode,
+                decode: self.decode,
+            }
+        }
+    }
+
+    /// Append-only file of batches, one JSON line each, read back in order. The file is
+    /// truncated whenever it has been read to the end, and batches left in it by an
+    /// earlier run are kept.
+    pub(super) struct SpillFile<M> {
+        config: SpillConfig<M>,
+        writer: File,
+        reader: BufReader<File>,
+        pending: usize,
+    }
+
+    impl<M> SpillFile<M> {
+        /// Opens the spill file, creating it if needed. A last line torn by a crash
+        /// mid-write is cut off.
+        pub(super) async fn open(config: SpillConfig<M>) -> Result<Self> {
+            let path = &config.path;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let writer = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await
+                .with_context(|| format!("failed to open spill file {}", path.display()))?;
+            let mut reader = BufReader::new(File::open(path).await?);
+
+            // Count the complete lines, then rewind to replay them
+            let (mut pending, mut complete) = (0, 0);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                let read = reader.read_until(b'\n', &mut line).await?;
+                if !line.ends_with(b"\n") {
+                    if read > 0 {
+                        warn!(path = %path.display(), bytes = read, "Cutting off a torn spill line");
+                        writer.set_len(complete).await?;
+                    }
+                    break;
+                }
+                pending += 1;
+                complete += read as u64;
+            }
+            reader.seek(SeekFrom::Start(0)).await?;
+
+            Ok(Self {
+                config,
+                writer,
+                reader,
+                pending,
+            })
+        }
+
+        /// Opens the spill file if an earlier run left one behind.
+        pub(super) async fn resume(config: SpillConfig<M>) -> Result<Option<Self>> {
+            match fs::try_exists(&config.path).await? {
+                true => Ok(Some(Self::open(config).await?)),
+                false => Ok(None),
+            }
+        }
+
+        pub(super) fn pending(&self) -> usize {
+            self.pending
+        }
+
+        pub(super) async fn push(&mut self, batch: &MessageBatch<M>) -> Result<()> {
+            let mut line = (self.config.encode)(batch)?;
+            line.push('\n');
+            self.writer.write_all(line.as_bytes()).await?;
+            self.writer.flush().await?;
+            self.pending += 1;
+            Ok(())
+        }
+
+        pub(super) async fn pop(&mut self) -> Result<Option<MessageBatch<M>>> {
+            if self.pending == 0 {
+                return Ok(None);
+            }
+            let mut line = String::new();
+            self.reader.read_line(&mut line).await?;
+            self.pending -= 1;
+            if self.pending == 0 {
+                self.writer.set_len(0).await?;
+                self.reader.seek(SeekFrom::Start(0)).await?;
+            }
+            Ok(Some((self.config.decode)(line.trim_end()).with_context(
+                || format!("corrupt spill file {}", self.config.path.display()),
+            )?))
+        }
+
+        /// Removes the file once everything spilled has been read back.
+        pub(super) async fn close(self) -> Result<()> {
+            if self.pending == 0 {
+                fs::remove_file(&self.config.path).await?;
+            }
+            Ok(())
+        }
+    }
+}
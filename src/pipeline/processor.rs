@@ -2,11 +2,29 @@
 
 use std::sync::Arc;
 
-use crate::core::{Message, MessageBatch, MessageSink};
+use tokio::time::{Duration, sleep};
+
+use crate::core::{Message, MessageBatch, MessageOutcome, MessageSink, PartialWrite};
+
+use super::PipelineStatus;
+
+/// Delay before the first retry of rejected messages; doubles with each attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Longest delay between retries of rejected messages.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
 
 /// Wraps a `MessageSink` and provides async batch processing.
+///
+/// Messages the sink rejects can be retried and then routed to a dead-letter sink;
+/// without either, rejections are only logged. Batches the sink fails as a whole are
+/// logged too, unless [`max_sink_errors`](Self::max_sink_errors) is set.
 pub struct MessageProcessor<M: Message> {
     sink: Arc<dyn MessageSink<M>>,
+    dead_letter: Option<Arc<dyn MessageSink<M>>>,
+    retries: u32,
+    max_sink_errors: Option<u32>,
+    status: Option<PipelineStatus>,
 }
 
 impl<M: Message> MessageProcessor<M> {
@@ -17,22 +35,197 @@ impl<M: Message> MessageProcessor<M> {
     {
         Self {
             sink: Arc::new(sink),
+            dead_letter: None,
+            retries: 0,
+            max_sink_errors: None,
+            status: None,
         }
     }
 
+    /// Sends messages that are still rejected after all retries to `sink`.
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: MessageSink<M>,
+    {
+        self.dead_letter = Some(Arc::new(sink));
+        self
+    }
+
+    /// Resubmits rejected messages up to `retries` times, with exponential backoff capped
+    /// at 30 seconds.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Stops with an error once `errors` consecutive batches failed in the sink, so a
+    /// supervisor can restart the pipeline; a delivered batch resets the count. Rows a
+    /// reachable sink failed to write ([`PartialWrite`]) do not count.
+    pub fn max_sink_errors(mut self, errors: u32) -> Self {
+        self.max_sink_errors = Some(errors);
+        self
+    }
+
+    /// Reports received batches and sink outcomes to `status`.
+    pub fn status(mut self, status: PipelineStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
     /// Consumes messages from the provided receiver and forwards them to the sink.
+    ///
+    /// Returns an error when the sink failed `max_sink_errors` batches in a row.
     pub async fn process_messages(
         &self,
         mut rx: tokio::sync::mpsc::Receiver<MessageBatch<M>>,
     ) -> anyhow::Result<()> {
         tracing::info!("Message processor started ({})", self.sink.name());
+        let mut sink_errors = 0;
         while let Some(batch) = rx.recv().await {
             tracing::debug!("Handling batch");
-            if let Err(err) = self.sink.handle_batch(batch).await {
-                tracing::warn!("{} sink error: {err}", self.sink.name());
+            if let Some(status) = &self.status {
+                status.record_batch(batch.len());
+            }
+            let sink_error = if self.dead_letter.is_none() && self.retries == 0 {
+                match self.sink.handle_batch(batch).await {
+                    Ok(()) => {
+                        self.report(PipelineStatus::record_delivered);
+                        None
+                    }
+                    Err(err) => self.sink_failure(&err),
+                }
+            } else {
+                self.deliver(batch).await
+            };
+
+            let Some(reason) = sink_error else {
+                sink_errors = 0;
+                continue;
+            };
+            sink_errors += 1;
+            if self.max_sink_errors.is_some_and(|max| sink_errors >= max) {
+                return Err(anyhow::anyhow!(
+                    "{} sink failed {sink_errors} batches in a row, last: {reason}",
+                    self.sink.name()
+                ));
             }
         }
         tracing::info!("Message processor stopped");
-        Ok(())
+        self.close().await
+    }
+
+    /// Closes the sink and the dead-letter sink once the source is done.
+    async fn close(&self) -> anyhow::Result<()> {
+        if let Some(dead_letter) = &self.dead_letter {
+            dead_letter.close().await?;
+        }
+        self.sink.close().await
+    }
+
+    /// Hands a batch to the sink, retrying rejected messages and dead-lettering the rest.
+    ///
+    /// Returns the sink error when its last attempt failed the batch as a whole.
+    async fn deliver(&self, batch: MessageBatch<M>) -> Option<String> {
+        let mut pending = batch;
+        let mut attempt = 0;
+        loop {
+            let (rejected, sink_error) = self.rejected(pending).await;
+            if rejected.is_empty() {
+                return None;
+            }
+
+            if attempt < self.retries {
+                attempt += 1;
+                tracing::warn!(
+                    sink = self.sink.name(),
+                    rejected = rejected.len(),
+                    attempt,
+                    "Retrying rejected messages"
+                );
+                sleep(retry_backoff(attempt)).await;
+                pending = rejected.into_iter().map(|(message, _)| message).collect();
+                continue;
+            }
+
+            self.report(|status| status.record_rejected(rejected.len()));
+            for (_, reason) in &rejected {
+                tracing::warn!(sink = self.sink.name(), reason = %reason, "Message rejected");
+            }
+            if let Some(dead_letter) = &self.dead_letter {
+                let count = rejected.len();
+                let messages = rejected.into_iter().map(|(message, _)| message).collect();
+                match dead_letter.handle_batch(messages).await {
+                    Ok(()) => tracing::info!(
+                        sink = dead_letter.name(),
+                        count,
+                        "Routed rejected messages to dead letter"
+                    ),
+                    Err(err) => {
+                        tracing::error!("{} dead-letter sink error: {err}", dead_letter.name())
+                    }
+                }
+            }
+            return sink_error;
+        }
     }
+
+    /// Messages of the batch the sink rejected, with the reasons, and the sink error when
+    /// the whole batch failed. A failed batch rejects every message, as does a missing
+    /// outcome.
+    async fn rejected(&self, batch: MessageBatch<M>) -> (Vec<(M, String)>, Option<String>) {
+        match self.sink.handle_batch_outcomes(batch.clone()).await {
+            Ok(outcomes) => {
+                self.report(PipelineStatus::record_delivered);
+                let rejected = batch
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(idx, message)| match outcomes.get(idx) {
+                        Some(
+                            MessageOutcome::Ok
+                            | MessageOutcome::Duplicate
+                            | MessageOutcome::Skipped(_),
+                        ) => None,
+                        Some(MessageOutcome::Rejected(reason)) => Some((message, reason.clone())),
+                        None => Some((message, "sink reported no outcome".to_string())),
+                    })
+                    .collect();
+                (rejected, None)
+            }
+            Err(err) => {
+                let sink_error = self.sink_failure(&err);
+                let reason = err.to_string();
+                let rejected = batch
+                    .into_iter()
+                    .map(|message| (message, reason.clone()))
+                    .collect();
+                (rejected, sink_error)
+            }
+        }
+    }
+
+    /// Reports a failed sink call. Returns the error when it points at the sink itself
+    /// rather than at some of the rows it wrote.
+    fn sink_failure(&self, err: &anyhow::Error) -> Option<String> {
+        let reason = err.to_string();
+        if err.downcast_ref::<PartialWrite>().is_some() {
+            tracing::warn!("{} sink failed to write rows: {reason}", self.sink.name());
+            self.report(|status| status.record_write_error(&reason));
+            return None;
+        }
+        tracing::warn!("{} sink error: {reason}", self.sink.name());
+        self.report(|status| status.record_sink_error(&reason));
+        Some(reason)
+    }
+
+    fn report(&self, update: impl FnOnce(&PipelineStatus)) {
+        if let Some(status) = &self.status {
+            update(status);
+        }
+    }
+}
+
+/// Delay before the given retry attempt, counted from 1.
+fn retry_backoff(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    RETRY_BACKOFF.saturating_mul(factor).min(MAX_RETRY_BACKOFF)
 }
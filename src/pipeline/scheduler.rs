@@ -0,0 +1,204 @@
+//! Re-runs batch sources on an interval, a cron expression or after the market close.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant, sleep};
+use tracing::{error, info, warn};
+
+use crate::config::ScheduleConfig;
+use crate::core::{Message, MessageBatch, MessageSource};
+
+use super::calendar::UsEquityCalendar;
+use super::status::PipelineStatus;
+
+/// Days searched for the next trading day; covers the longest holiday stretches.
+const MAX_CLOSED_DAYS: i64 = 10;
+
+/// When a scheduled source runs.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// Every `interval`, measured from the start of each run; the first run is immediate
+    Interval(Duration),
+    /// Standard five-field cron expression evaluated in `timezone`
+    Cron { cron: Cron, timezone: Tz },
+    /// The given offset after the US equity close, on trading days only
+    AfterMarketClose(Duration),
+}
+
+impl Schedule {
+    /// Parses a five-field cron expression (`minute hour day month weekday`).
+    pub fn cron(expression: &str, timezone: Tz) -> Result<Self> {
+        let cron = Cron::new(expression)
+            .parse()
+            .map_err(|err| anyhow!("invalid cron expression {expression:?}: {err}"))?;
+        Ok(Schedule::Cron { cron, timezone })
+    }
+
+    /// Builds a schedule from its config section, which must set exactly one kind.
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let timezone = match &config.timezone {
+            Some(name) => Tz::from_str(name).map_err(|_| anyhow!("unknown time zone {name:?}"))?,
+            None => Tz::UTC,
+        };
+        match (
+            config.interval_secs,
+            &config.cron,
+            config.after_market_close_mins,
+        ) {
+            (Some(0), None, None) => Err(anyhow!("schedule interval_secs must be positive")),
+            (Some(secs), None, None) => Ok(Schedule::Interval(Duration::from_secs(secs))),
+            (None, Some(expression), None) => Schedule::cron(expression, timezone),
+            (None, None, Some(mins)) => {
+                Ok(Schedule::AfterMarketClose(Duration::from_secs(mins * 60)))
+            }
+            _ => Err(anyhow!(
+                "schedule needs exactly one of interval_secs, cron or after_market_close_mins"
+            )),
+        }
+    }
+
+    /// The first run strictly after `after`, or `None` when the schedule never fires.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval(interval) => Some(after + ChronoDuration::from_std(*interval).ok()?),
+            Schedule::Cron { cron, timezone } => cron
+                .find_next_occurrence(&after.with_timezone(timezone), false)
+                .ok()
+                .map(|next| next.with_timezone(&Utc)),
+            Schedule::AfterMarketClose(offset) => {
+                let offset = ChronoDuration::from_std(*offset).ok()?;
+                let today = after
+                    .with_timezone(&chrono_tz::America::New_York)
+                    .date_naive();
+                (0..MAX_CLOSED_DAYS)
+                    .filter_map(|days| UsEquityCalendar::close(today + ChronoDuration::days(days)))
+                    .map(|close| close + offset)
+                    .find(|run| *run > after)
+            }
+        }
+    }
+}
+
+/// Wraps a batch source factory and re-runs a fresh source on every scheduled tick.
+///
+/// Runs never overlap: ticks that pass while a run is still active are skipped. A
+/// failed run is logged and the schedule carries on.
+pub struct ScheduledSource<Src, F> {
+    schedule: Schedule,
+    factory: F,
+    max_runs: Option<usize>,
+    status: Option<PipelineStatus>,
+    _marker: std::marker::PhantomData<fn() -> Src>,
+}
+
+impl<Src, F> ScheduledSource<Src, F> {
+    /// Creates a scheduled source; `factory` builds the source for each run.
+    pub fn new(schedule: Schedule, factory: F) -> Self {
+        Self {
+            schedule,
+            factory,
+            max_runs: None,
+            status: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Stops after `runs` runs instead of running forever.
+    pub fn max_runs(mut self, runs: usize) -> Self {
+        self.max_runs = Some(runs);
+        self
+    }
+
+    /// Records failed runs on `status`, so readiness reports them.
+    pub fn status(mut self, status: PipelineStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    async fn schedule<M>(&mut self, tx: mpsc::Sender<MessageBatch<M>>) -> Result<()>
+    where
+        M: Message,
+        Src: MessageSource<M>,
+        F: FnMut() -> Result<Src>,
+    {
+        let mut next = match self.schedule {
+            Schedule::Interval(_) => Utc::now(),
+            _ => self.next_after(Utc::now())?,
+        };
+        let mut runs = 0;
+
+        while self.max_runs.is_none_or(|max| runs < max) && !tx.is_closed() {
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            info!(next = %next, "Waiting for the next scheduled run");
+            sleep(wait).await;
+
+            let started = Instant::now();
+            let failure = match (self.factory)() {
+                Ok(mut source) => match source.run(tx.clone()).await {
+                    Ok(()) => {
+                        info!(elapsed = ?started.elapsed(), "Scheduled run finished");
+                        None
+                    }
+                    Err(err) => {
+                        error!("Scheduled run failed: {err:#}");
+                        Some(format!("scheduled run failed: {err:#}"))
+                    }
+                },
+                Err(err) => {
+                    error!("Failed to build scheduled source: {err:#}");
+                    Some(format!("failed to build scheduled source: {err:#}"))
+                }
+            };
+            if let Some(status) = &self.status {
+                match &failure {
+                    Some(failure) => status.record_run_failed(failure),
+                    None => status.record_run_succeeded(),
+                }
+            }
+            runs += 1;
+
+            let now = Utc::now();
+            let mut skipped = 0;
+            next = self.next_after(next)?;
+            while next <= now {
+                skipped += 1;
+                next = self.next_after(next)?;
+            }
+            if skipped > 0 {
+                warn!(
+                    skipped,
+                    "Previous run was still active, skipped scheduled runs"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        self.schedule
+            .next_after(after)
+            .ok_or_else(|| anyhow!("schedule {:?} has no run after {after}", self.schedule))
+    }
+}
+
+impl<M, Src, F> MessageSource<M> for ScheduledSource<Src, F>
+where
+    M: Message,
+    Src: MessageSource<M>,
+    F: FnMut() -> Result<Src> + Send + 'static,
+{
+    fn run<'a>(
+        &'a mut self,
+        tx: mpsc::Sender<MessageBatch<M>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(self.schedule(tx))
+    }
+}
@@ -0,0 +1,285 @@
+//! Live pipeline state shared with health checks and status reporting.
+//!
+//! The feed, processor and sources update a [`PipelineStatus`] as they work, so
+//! readiness reflects what the pipeline last observed rather than a separate probe.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use tokio::sync::mpsc;
+
+/// Share of the channel capacity above which a pipeline is reported as saturated.
+const SATURATION_PERCENT: usize = 90;
+
+/// Reports the queued batches and the capacity of a pipeline channel.
+type ChannelProbe = Box<dyn Fn() -> Option<(usize, usize)> + Send + Sync>;
+
+/// Shared, cheaply cloneable state of one pipeline.
+#[derive(Clone)]
+pub struct PipelineStatus {
+    name: Arc<str>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    running: bool,
+    finished: bool,
+    connected: Option<bool>,
+    run_failed: bool,
+    sink_reachable: Option<bool>,
+    last_message_at: Option<SystemTime>,
+    messages: u64,
+    batches: u64,
+    rejected: u64,
+    dropped: u64,
+    spilled: u64,
+    errors: u64,
+    last_error: Option<String>,
+    channel: Option<ChannelProbe>,
+    buffered: Option<(usize, usize)>,
+}
+
+/// Point-in-time copy of a [`PipelineStatus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineSnapshot {
+    pub name: String,
+    /// Whether the source is currently running
+    pub running: bool,
+    /// Whether the pipeline completed and will not run again
+    pub finished: bool,
+    /// Connection state for sources that report one, such as websockets
+    pub connected: Option<bool>,
+    /// Whether the last run of a scheduled source failed
+    pub last_run_failed: bool,
+    /// Outcome of the last sink write; `None` before the first batch
+    pub sink_reachable: Option<bool>,
+    pub last_message_at: Option<SystemTime>,
+    pub messages: u64,
+    pub batches: u64,
+    /// Messages the sink rejected
+    pub rejected: u64,
+    /// Messages discarded or conflated away by the overflow policy
+    pub dropped: u64,
+    /// Messages the overflow policy wrote to disk
+    pub spilled: u64,
+    /// Failed sink writes and source errors
+    pub errors: u64,
+    pub last_error: Option<String>,
+    /// Batches waiting in the channel, or in the overflow relay when there is one
+    pub queued: usize,
+    pub capacity: usize,
+}
+
+impl PipelineStatus {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            state: Arc::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Marks the source as started or stopped.
+    pub fn set_running(&self, running: bool) {
+        let mut state = self.lock();
+        state.running = running;
+        if running {
+            state.finished = false;
+        }
+    }
+
+    /// Marks the pipeline as completed for good, e.g. a one-shot backfill; it no longer
+    /// counts against readiness.
+    pub fn set_finished(&self) {
+        self.lock().finished = true;
+    }
+
+    /// Records whether a connection-based source is currently connected.
+    pub fn set_connected(&self, connected: bool) {
+        self.lock().connected = Some(connected);
+    }
+
+    /// Records a scheduled run that completed; clears an earlier failed run.
+    pub fn record_run_succeeded(&self) {
+        self.lock().run_failed = false;
+    }
+
+    /// Records a scheduled run that failed; the pipeline is unready until a run succeeds.
+    pub fn record_run_failed(&self, error: &str) {
+        let mut state = self.lock();
+        state.run_failed = true;
+        state.errors += 1;
+        state.last_error = Some(error.to_string());
+    }
+
+    /// Records a batch of `messages` received from the source.
+    pub fn record_batch(&self, messages: usize) {
+        let mut state = self.lock();
+        state.batches += 1;
+        state.messages += messages as u64;
+        state.last_message_at = Some(SystemTime::now());
+    }
+
+    /// Records a sink write that succeeded, even if the sink rejected some messages.
+    pub fn record_delivered(&self) {
+        self.lock().sink_reachable = Some(true);
+    }
+
+    /// Records messages the sink rejected for good, after any retries.
+    pub fn record_rejected(&self, messages: usize) {
+        self.lock().rejected += messages as u64;
+    }
+
+    /// Records messages the overflow policy discarded.
+    pub fn record_dropped(&self, messages: usize) {
+        self.lock().dropped += messages as u64;
+    }
+
+    /// Records messages the overflow policy spilled to disk.
+    pub fn record_spilled(&self, messages: usize) {
+        self.lock().spilled += messages as u64;
+    }
+
+    /// Records a failed sink write.
+    pub fn record_sink_error(&self, error: &str) {
+        let mut state = self.lock();
+        state.sink_reachable = Some(false);
+        state.errors += 1;
+        state.last_error = Some(error.to_string());
+    }
+
+    /// Records rows a reachable sink failed to write, such as constraint violations.
+    pub fn record_write_error(&self, error: &str) {
+        let mut state = self.lock();
+        state.sink_reachable = Some(true);
+        state.errors += 1;
+        state.last_error = Some(error.to_string());
+    }
+
+    /// Records a source error; the pipeline keeps its sink state.
+    pub fn record_error(&self, error: &str) {
+        let mut state = self.lock();
+        state.errors += 1;
+        state.last_error = Some(error.to_string());
+    }
+
+    /// Tracks the fill level of the channel `tx` sends on, without keeping it open.
+    pub fn watch_channel<T: Send + 'static>(&self, tx: &mpsc::Sender<T>) {
+        let weak = tx.downgrade();
+        self.lock().channel = Some(Box::new(move || {
+            weak.upgrade()
+                .map(|tx| (tx.max_capacity() - tx.capacity(), tx.max_capacity()))
+        }));
+    }
+
+    /// Records the batches held by an overflow relay and its capacity, reported instead
+    /// of the channel fill level.
+    pub fn record_buffered(&self, queued: usize, capacity: usize) {
+        self.lock().buffered = Some((queued, capacity));
+    }
+
+    pub fn snapshot(&self) -> PipelineSnapshot {
+        let state = self.lock();
+        let (queued, capacity) = state
+            .buffered
+            .or_else(|| state.channel.as_ref().and_then(|probe| probe()))
+            .unwrap_or_default();
+        PipelineSnapshot {
+            name: self.name.to_string(),
+            running: state.running,
+            finished: state.finished,
+            connected: state.connected,
+            last_run_failed: state.run_failed,
+            sink_reachable: state.sink_reachable,
+            last_message_at: state.last_message_at,
+            messages: state.messages,
+            batches: state.batches,
+            rejected: state.rejected,
+            dropped: state.dropped,
+            spilled: state.spilled,
+            errors: state.errors,
+            last_error: state.last_error.clone(),
+            queued,
+            capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl PipelineSnapshot {
+    /// Reasons the pipeline is not ready; empty when it is.
+    ///
+    /// A pipeline is ready while its source runs and is connected, its last scheduled
+    /// run succeeded, its sink accepted the last write and its channel is not
+    /// saturated. A finished pipeline is always ready.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.finished {
+            return problems;
+        }
+        if !self.running {
+            problems.push(format!("{}: source is not running", self.name));
+        } else if self.connected == Some(false) {
+            problems.push(format!("{}: source is disconnected", self.name));
+        }
+        if self.last_run_failed {
+            let error = self.last_error.as_deref().unwrap_or("unknown error");
+            problems.push(format!("{}: last scheduled run failed: {error}", self.name));
+        }
+        if self.sink_reachable == Some(false) {
+            let error = self.last_error.as_deref().unwrap_or("unknown error");
+            problems.push(format!("{}: sink is unreachable: {error}", self.name));
+        }
+        if self.capacity > 0 && self.queued * 100 >= self.capacity * SATURATION_PERCENT {
+            problems.push(format!(
+                "{}: channel is saturated ({}/{})",
+                self.name, self.queued, self.capacity
+            ));
+        }
+        problems
+    }
+}
+
+/// The statuses of every pipeline in the process, keyed by pipeline name.
+#[derive(Clone, Default)]
+pub struct StatusRegistry {
+    pipelines: Arc<Mutex<BTreeMap<String, PipelineStatus>>>,
+}
+
+impl StatusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The status of `name`, created on first use; restarts of a pipeline share it.
+    pub fn pipeline(&self, name: &str) -> PipelineStatus {
+        self.pipelines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(name.to_string())
+            .or_insert_with(|| PipelineStatus::new(name))
+            .clone()
+    }
+
+    /// Snapshots of every registered pipeline, ordered by name.
+    pub fn snapshots(&self) -> Vec<PipelineSnapshot> {
+        let pipelines: Vec<PipelineStatus> = self
+            .pipelines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        pipelines.iter().map(PipelineStatus::snapshot).collect()
+    }
+}
@@ -0,0 +1,178 @@
+//! Runs several pipelines concurrently and restarts them according to their policy.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use tokio::time::{Duration, Instant, sleep};
+use tracing::{error, info, warn};
+
+use super::{PipelineStatus, StatusRegistry};
+
+/// Longest wait between restarts; the delay doubles after each restart up to this.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+
+type PipelineFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// When a finished pipeline is started again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Restart {
+    /// Run once
+    #[default]
+    Never,
+    /// Restart after an error or panic
+    OnFailure,
+    /// Restart whenever the pipeline stops, e.g. a backfill run on repeat
+    Always,
+}
+
+/// Restart behaviour of a supervised pipeline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestartPolicy {
+    pub policy: Restart,
+    /// Restarts allowed before giving up; unlimited when unset
+    pub max_restarts: Option<u32>,
+    /// Wait before the first restart, doubled for each consecutive restart
+    pub delay_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            policy: Restart::Never,
+            max_restarts: None,
+            delay_secs: 5,
+        }
+    }
+}
+
+struct Supervised {
+    name: String,
+    policy: RestartPolicy,
+    factory: Box<dyn Fn() -> PipelineFuture + Send + Sync>,
+}
+
+/// Runs named pipelines concurrently, each under its own restart policy.
+///
+/// The pipeline future is recreated from its factory on every restart, so sources and
+/// sinks are rebuilt from scratch.
+#[derive(Default)]
+pub struct Supervisor {
+    pipelines: Vec<Supervised>,
+    registry: Option<StatusRegistry>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pipeline; `factory` builds and runs it to completion.
+    pub fn pipeline<F, Fut>(mut self, name: &str, policy: RestartPolicy, factory: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.pipelines.push(Supervised {
+            name: name.to_string(),
+            policy,
+            factory: Box::new(move || Box::pin(factory())),
+        });
+        self
+    }
+
+    /// Marks pipelines that completed for good as finished in `registry`, so one-shot
+    /// pipelines stop counting against readiness once done.
+    pub fn status(mut self, registry: StatusRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Runs every pipeline until it stops for good.
+    ///
+    /// Returns an error naming each pipeline that ended with a failure.
+    pub async fn run(self) -> Result<()> {
+        let registry = self.registry;
+        let handles: Vec<_> = self
+            .pipelines
+            .into_iter()
+            .map(|pipeline| {
+                let status = registry
+                    .as_ref()
+                    .map(|registry| registry.pipeline(&pipeline.name));
+                tokio::spawn(supervise(pipeline, status))
+            })
+            .collect();
+
+        let mut failures = Vec::new();
+        for handle in handles {
+            if let Err(err) = handle.await? {
+                failures.push(format!("{err:#}"));
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(anyhow!(
+                "{} pipeline(s) failed:\n  - {}",
+                failures.len(),
+                failures.join("\n  - ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+async fn supervise(pipeline: Supervised, status: Option<PipelineStatus>) -> Result<()> {
+    let Supervised {
+        name,
+        policy,
+        factory,
+    } = pipeline;
+    let base_delay = Duration::from_secs(policy.delay_secs);
+    let mut delay = base_delay;
+    let mut restarts = 0;
+
+    loop {
+        info!(pipeline = %name, "Starting pipeline");
+        let started = Instant::now();
+        // Spawned so a panicking pipeline is reported instead of taking down the others
+        let failure = match tokio::spawn(factory()).await {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(format!("{err:#}")),
+            Err(err) => Some(format!("panicked: {err}")),
+        };
+        match &failure {
+            Some(reason) => error!(pipeline = %name, "Pipeline failed: {reason}"),
+            None => info!(pipeline = %name, "Pipeline finished"),
+        }
+
+        let restart = match policy.policy {
+            Restart::Never => false,
+            Restart::OnFailure => failure.is_some(),
+            Restart::Always => true,
+        };
+        if !restart || policy.max_restarts.is_some_and(|max| restarts >= max) {
+            return match failure {
+                Some(reason) => Err(anyhow!("pipeline {name:?}: {reason}")),
+                None => {
+                    if let Some(status) = &status {
+                        status.set_finished();
+                    }
+                    Ok(())
+                }
+            };
+        }
+
+        // A pipeline that ran for a while earns a fresh backoff
+        if started.elapsed() > MAX_RESTART_DELAY {
+            delay = base_delay;
+        }
+        restarts += 1;
+        warn!(pipeline = %name, restarts, delay = ?delay, "Restarting pipeline");
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_RESTART_DELAY);
+    }
+}
@@ -0,0 +1,107 @@
+//! Credential handling that keeps secrets out of `Debug` output and logs.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer};
+
+/// Placeholder printed instead of a secret or the credentials in a URL.
+const REDACTED: &str = "***";
+
+/// A credential whose `Debug` and `Display` output is redacted.
+///
+/// The value is only reachable through [`Secret::expose`], so it cannot end up in a log
+/// line by accident. In config files a secret is either a plain string or
+/// `{ file = "/run/secrets/name" }`, which reads the value from a mounted secret file.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Reads a secret from a file, ignoring surrounding whitespace such as the trailing
+    /// newline most secret mounts include.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let value = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read secret file {}", path.display()))?;
+        Ok(Self(value.trim().to_string()))
+    }
+
+    /// The secret value; never pass it to a log macro.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Value(String),
+            File { file: PathBuf },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Value(value) => Ok(Secret(value)),
+            Repr::File { file } => {
+                Secret::from_file(&file).map_err(|err| serde::de::Error::custom(format!("{err:#}")))
+            }
+        }
+    }
+}
+
+/// Replaces the `user:password@` part of every URL or proxy address in `text`.
+///
+/// Used for messages that may echo a proxy or connection URL, such as client errors.
+pub fn redact_credentials(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(redact_token)
+        .collect()
+}
+
+fn redact_token(token: &str) -> String {
+    let Some(at) = token.rfind('@') else {
+        return token.to_string();
+    };
+    let prefix = &token[..at];
+    let start = match prefix.rfind("://") {
+        Some(scheme) => scheme + 3,
+        None => prefix
+            .rfind(['(', '"', '\'', '<', '=', '['])
+            .map_or(0, |idx| idx + 1),
+    };
+    let userinfo = &prefix[start..];
+    if userinfo.is_empty() || userinfo.contains('/') {
+        return token.to_string();
+    }
+    format!("{}{REDACTED}{}", &token[..start], &token[at..])
+}
@@ -0,0 +1,81 @@
+//! PostgreSQL-backed checkpoint store.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use tokio_postgres::Client;
+
+use crate::core::CheckpointStore;
+
+type AsyncResult<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Stores job positions in the `tickflow_checkpoints` table.
+pub struct PostgresCheckpointStore {
+    client: Arc<Client>,
+}
+
+impl PostgresCheckpointStore {
+    /// Creates the store, typically sharing the client of a [`Database`](super::Database).
+    pub fn new(client: Arc<Client>) -> Self {
+        Self { client }
+    }
+
+    /// Creates the checkpoint table if it does not exist.
+    pub async fn initialize_schema(&self) -> Result<(), tokio_postgres::Error> {
+        self.client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS tickflow_checkpoints (
+                    job VARCHAR(128) PRIMARY KEY,
+                    position TEXT NOT NULL,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                )",
+                &[],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl CheckpointStore for PostgresCheckpointStore {
+    fn load<'a>(&'a self, job: &'a str) -> AsyncResult<'a, Option<String>> {
+        Box::pin(async move {
+            let row = self
+                .client
+                .query_opt(
+                    "SELECT position FROM tickflow_checkpoints WHERE job = $1",
+                    &[&job],
+                )
+                .await
+                .with_context(|| format!("Failed to load checkpoint {job}"))?;
+            Ok(row.map(|row| row.get("position")))
+        })
+    }
+
+    fn save<'a>(&'a self, job: &'a str, position: &'a str) -> AsyncResult<'a, ()> {
+        Box::pin(async move {
+            self.client
+                .execute(
+                    "INSERT INTO tickflow_checkpoints (job, position) VALUES ($1, $2)
+                     ON CONFLICT (job) DO UPDATE SET
+                        position = EXCLUDED.position,
+                        updated_at = CURRENT_TIMESTAMP",
+                    &[&job, &position],
+                )
+                .await
+                .with_context(|| format!("Failed to save checkpoint {job}"))?;
+            Ok(())
+        })
+    }
+
+    fn clear<'a>(&'a self, job: &'a str) -> AsyncResult<'a, ()> {
+        Box::pin(async move {
+            self.client
+                .execute("DELETE FROM tickflow_checkpoints WHERE job = $1", &[&job])
+                .await
+                .with_context(|| format!("Failed to clear checkpoint {job}"))?;
+            Ok(())
+        })
+    }
+}
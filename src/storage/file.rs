@@ -0,0 +1,322 @@
+//! Append-only file sink that captures raw messages as JSON lines or CSV.
+
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{NaiveDate, Utc};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use tracing::info;
+
+use crate::core::{Message, MessageBatch, MessageSink};
+
+/// Output encoding used for captured messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// One JSON document per line.
+    JsonLines,
+    /// `received_at,message` rows where `message` is the JSON-encoded payload.
+    Csv,
+}
+
+impl FileFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            FileFormat::JsonLines => "jsonl",
+            FileFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Sink that appends every message to rotating, optionally gzip-compressed files.
+///
+/// Files are named `{prefix}-{YYYYMMDD}-{seq}.{ext}[.gz]` and always rotate when the
+/// UTC day changes. An optional size limit starts a new sequence within the same day.
+pub struct FileSink<M> {
+    options: FileOptions,
+    state: Arc<Mutex<Option<OpenFile>>>,
+    _marker: PhantomData<fn(M)>,
+}
+
+impl<M> FileSink<M> {
+    /// Creates a sink writing into `dir` with the given format and default options.
+    pub fn new(dir: impl Into<PathBuf>, format: FileFormat) -> Self {
+        Self {
+            options: FileOptions {
+                dir: dir.into(),
+                prefix: "capture".to_string(),
+                format,
+                gzip: false,
+                max_file_size: None,
+            },
+            state: Arc::new(Mutex::new(None)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Overrides the file name prefix (defaults to `capture`).
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.options.prefix = prefix.to_string();
+        self
+    }
+
+    /// Enables gzip compression of the output files.
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.options.gzip = enabled;
+        self
+    }
+
+    /// Rotates to a new file once the current one reaches `bytes` on disk.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.options.max_file_size = Some(bytes);
+        self
+    }
+}
+
+/// Naming and rotation settings shared with the blocking writer task.
+#[derive(Clone)]
+struct FileOptions {
+    dir: PathBuf,
+    prefix: String,
+    format: FileFormat,
+    gzip: bool,
+    max_file_size: Option<u64>,
+}
+
+impl FileOptions {
+    fn file_name(&self, day: NaiveDate, seq: u32) -> String {
+        let mut name = format!(
+            "{}-{}-{seq:04}.{}",
+            self.prefix,
+            day.format("%Y%m%d"),
+            self.format.extension()
+        );
+        if self.gzip {
+            name.push_str(".gz");
+        }
+        name
+    }
+
+    fn is_full(&self, size: u64) -> bool {
+        self.max_file_size.is_some_and(|max| size >= max)
+    }
+
+    /// Opens the latest file for `day`, moving past sequences that are already full.
+    fn open(&self, day: NaiveDate, mut seq: u32) -> Result<OpenFile> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create capture directory {:?}", self.dir))?;
+
+        loop {
+            let path = self.dir.join(self.file_name(day, seq));
+            let existing = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if self.is_full(existing) {
+                seq += 1;
+                continue;
+            }
+            return OpenFile::create(&path, day, seq, existing, self.gzip);
+        }
+    }
+
+    fn write_batch(&self, state: &Mutex<Option<OpenFile>>, lines: Vec<String>) -> Result<()> {
+        let mut guard = state
+            .lock()
+            .map_err(|_| anyhow!("file sink state poisoned"))?;
+        let today = Utc::now().date_naive();
+
+        let needs_rotation = match guard.as_ref() {
+            None => true,
+            Some(file) => file.day != today || self.is_full(file.size()),
+        };
+
+        if needs_rotation {
+            let next_seq = match guard.take() {
+                Some(file) if file.day == today => {
+                    let seq = file.seq + 1;
+                    file.finish()?;
+                    seq
+                }
+                Some(file) => {
+                    file.finish()?;
+                    0
+                }
+                None => 0,
+            };
+            let file = self.open(today, next_seq)?;
+            info!(path = ?file.path, "Opened capture file");
+            *guard = Some(file);
+        }
+
+        let file = guard.as_mut().expect("capture file opened above");
+        if file.size() == 0 && self.format == FileFormat::Csv {
+            file.writer.write_all(b"received_at,message\n")?;
+        }
+        for line in lines {
+            file.writer.write_all(line.as_bytes())?;
+            file.writer.write_all(b"\n")?;
+        }
+        file.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<M> MessageSink<M> for FileSink<M>
+where
+    M: Message + Serialize,
+{
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        batch: MessageBatch<M>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let received_at = Utc::now().to_rfc3339();
+            let lines = batch
+                .iter()
+                .map(|message| {
+                    let json = serde_json::to_string(message)?;
+                    Ok(match self.options.format {
+                        FileFormat::JsonLines => json,
+                        FileFormat::Csv => format!("{received_at},{}", csv_escape(&json)),
+                    })
+                })
+                .collect::<Result<Vec<String>, serde_json::Error>>()
+                .context("failed to serialize message")?;
+
+            let options = self.options.clone();
+            let state = Arc::clone(&self.state);
+            tokio::task::spawn_blocking(move || options.write_batch(&state, lines))
+                .await
+                .context("file sink writer task panicked")?
+        })
+    }
+
+    fn close<'a>(&'a self) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let state = Arc::clone(&self.state);
+            tokio::task::spawn_blocking(move || close_file(&state))
+                .await
+                .context("file sink writer task panicked")?
+        })
+    }
+}
+
+/// Finishes the open capture file, if any, so a gzip stream gets its trailer.
+fn close_file(state: &Mutex<Option<OpenFile>>) -> Result<()> {
+    let file = state
+        .lock()
+        .map_err(|_| anyhow!("file sink state poisoned"))?
+        .take();
+    if let Some(file) = file {
+        let path = file.path.clone();
+        file.finish()
+            .with_context(|| format!("failed to finish capture file {path:?}"))?;
+        info!(path = ?path, "Closed capture file");
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field, doubling any embedded quotes.
+fn csv_escape(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+/// Currently open capture file and its rotation bookkeeping.
+struct OpenFile {
+    path: PathBuf,
+    day: NaiveDate,
+    seq: u32,
+    writer: CaptureWriter,
+}
+
+impl OpenFile {
+    fn create(path: &Path, day: NaiveDate, seq: u32, existing: u64, gzip: bool) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open capture file {path:?}"))?;
+        let counting = CountingWriter {
+            inner: file,
+            written: existing,
+        };
+        let writer = if gzip {
+            CaptureWriter::Gzip(GzEncoder::new(
+                BufWriter::new(counting),
+                Compression::default(),
+            ))
+        } else {
+            CaptureWriter::Plain(BufWriter::new(counting))
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            day,
+            seq,
+            writer,
+        })
+    }
+
+    /// Bytes flushed to disk so far, including content present before opening.
+    fn size(&self) -> u64 {
+        match &self.writer {
+            CaptureWriter::Plain(w) => w.get_ref().written,
+            CaptureWriter::Gzip(w) => w.get_ref().get_ref().written,
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self.writer {
+            CaptureWriter::Plain(mut w) => w.flush(),
+            CaptureWriter::Gzip(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+enum CaptureWriter {
+    Plain(BufWriter<CountingWriter>),
+    Gzip(GzEncoder<BufWriter<CountingWriter>>),
+}
+
+impl Write for CaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CaptureWriter::Plain(w) => w.write(buf),
+            CaptureWriter::Gzip(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CaptureWriter::Plain(w) => w.flush(),
+            CaptureWriter::Gzip(w) => w.flush(),
+        }
+    }
+}
+
+/// File wrapper tracking how many bytes reached the underlying file.
+struct CountingWriter {
+    inner: File,
+    written: u64,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -1,10 +1,22 @@
 //! Storage integrations for message sinks.
 
+#[cfg(feature = "postgres")]
+pub mod checkpoint;
+
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
 #[cfg(feature = "postgres")]
 pub mod postgres_handler;
 
+#[cfg(feature = "file")]
+pub mod file;
+
 #[cfg(feature = "postgres")]
-pub use postgres::Database;
+pub use checkpoint::PostgresCheckpointStore;
+
+#[cfg(feature = "postgres")]
+pub use postgres::{BatchOutcome, Database, InsertOutcome};
+
+#[cfg(feature = "file")]
+pub use file::{FileFormat, FileSink};
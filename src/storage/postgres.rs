@@ -6,9 +6,9 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use tokio_postgres::{Client, NoTls};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::core::{Message, MessageBatch, MessageSink};
+use crate::core::{Message, MessageBatch, MessageOutcome, MessageSink, PartialWrite};
 
 // Type aliases to reduce verbosity
 type AsyncResult<T> = Pin<Box<dyn Future<Output = Result<T>> + Send>>;
@@ -22,6 +22,122 @@ pub trait DatabaseMessageHandler<M: Message>: Send + Sync + 'static {
 
     /// Insert a batch of messages into the database.
     fn insert_batch(&self, client: Arc<Client>, batch: Vec<M>) -> AsyncResult<()>;
+
+    /// Insert a batch and report one outcome per message, in batch order.
+    ///
+    /// The default adapter reports every message as ok once `insert_batch` succeeds.
+    fn insert_batch_outcomes(
+        &self,
+        client: Arc<Client>,
+        batch: Vec<M>,
+    ) -> AsyncResult<Vec<MessageOutcome>> {
+        let len = batch.len();
+        let insert = self.insert_batch(client, batch);
+        Box::pin(async move {
+            insert.await?;
+            Ok(vec![MessageOutcome::Ok; len])
+        })
+    }
+}
+
+/// Result of writing a single row.
+#[derive(Debug)]
+pub enum InsertOutcome {
+    Inserted,
+    /// The row already existed and was left unchanged
+    Duplicate,
+    /// The row was not written because it is incomplete or malformed
+    Skipped(String),
+    /// The database rejected the row
+    Failed(String),
+}
+
+impl InsertOutcome {
+    /// Maps the result of an `INSERT ... ON CONFLICT DO NOTHING` to an outcome; `context`
+    /// describes the row in the failure message.
+    pub fn from_execute(result: Result<u64, tokio_postgres::Error>, context: &str) -> Self {
+        match result {
+            Ok(0) => InsertOutcome::Duplicate,
+            Ok(_) => InsertOutcome::Inserted,
+            Err(e) => InsertOutcome::Failed(format!("{context}: {e}")),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, InsertOutcome::Failed(_))
+    }
+}
+
+/// Row outcomes of a batch rolled up into counts.
+#[derive(Debug, Default)]
+pub struct BatchOutcome {
+    pub inserted: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Messages of the failed rows
+    pub errors: Vec<String>,
+    /// Outcome of each recorded row, in order
+    pub messages: Vec<MessageOutcome>,
+}
+
+impl BatchOutcome {
+    /// Counts the outcome of a message's row, logging skipped and failed rows.
+    pub fn record(&mut self, outcome: InsertOutcome) {
+        let message = match outcome {
+            InsertOutcome::Inserted => {
+                self.inserted += 1;
+                MessageOutcome::Ok
+            }
+            InsertOutcome::Duplicate => {
+                self.duplicates += 1;
+                MessageOutcome::Duplicate
+            }
+            InsertOutcome::Skipped(reason) => {
+                warn!("Skipping {reason}");
+                self.skipped += 1;
+                MessageOutcome::Skipped(reason)
+            }
+            InsertOutcome::Failed(e) => {
+                self.record_error(e.clone());
+                MessageOutcome::Rejected(format!("failed to insert {e}"))
+            }
+        };
+        self.messages.push(message);
+    }
+
+    /// Counts a failed derived row, such as a TTM roll-up, and rejects the messages at
+    /// `sources` it was derived from so they are retried.
+    pub fn record_derived_error(&mut self, e: String, sources: &[usize]) {
+        for &idx in sources {
+            if let Some(message) = self.messages.get_mut(idx)
+                && !message.is_rejected()
+            {
+                *message = MessageOutcome::Rejected(format!("failed to insert {e}"));
+            }
+        }
+        self.record_error(e);
+    }
+
+    fn record_error(&mut self, e: String) {
+        error!("Failed to insert {e}");
+        self.failed += 1;
+        self.errors.push(e);
+    }
+
+    /// Errors with a [`PartialWrite`] when any row failed, so the failure reaches the
+    /// processor without marking the database unreachable.
+    pub fn into_result(self) -> Result<Self> {
+        match self.errors.first() {
+            None => Ok(self),
+            Some(first) => Err(PartialWrite {
+                failed: self.failed,
+                total: self.inserted + self.duplicates + self.skipped + self.failed,
+                first: first.clone(),
+            }
+            .into()),
+        }
+    }
 }
 
 /// PostgreSQL database sink for market data messages.
@@ -55,6 +171,11 @@ impl<M: Message> Database<M> {
         })
     }
 
+    /// Returns a shared handle to the underlying PostgreSQL client.
+    pub fn client(&self) -> Arc<Client> {
+        Arc::clone(&self.client)
+    }
+
     /// Initialize the database schema for the message type.
     pub async fn initialize_schema(&self) -> Result<(), tokio_postgres::Error> {
         info!("Initializing database schema...");
@@ -78,6 +199,14 @@ impl<M: Message> MessageSink<M> for Database<M> {
         let client = Arc::clone(&self.client);
         Box::pin(async move { self.handler.insert_batch(client, batch).await })
     }
+
+    fn handle_batch_outcomes<'a>(
+        &'a self,
+        batch: MessageBatch<M>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<MessageOutcome>>> + Send + 'a>> {
+        let client = Arc::clone(&self.client);
+        Box::pin(async move { self.handler.insert_batch_outcomes(client, batch).await })
+    }
 }
 
 // Re-export message handlers
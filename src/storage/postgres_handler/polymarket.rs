@@ -7,9 +7,14 @@ use std::sync::Arc;
 use anyhow::Result;
 use chrono::NaiveDateTime;
 use tokio_postgres::Client;
+use tokio_postgres::types::Json;
 use tracing::{error, info};
 
-use crate::connectors::polymarket::types::{Market, PolymarketMessage};
+use crate::connectors::polymarket::sync::BaselineLoader;
+use crate::connectors::polymarket::types::{
+    BookSnapshot, LastTrade, Market, MarketEvent, PolymarketMessage, PriceChange, PricePoint,
+    PriceSnapshot, TickSizeChange,
+};
 use crate::storage::postgres::DatabaseMessageHandler;
 
 pub struct PolymarketMessageHandler;
@@ -64,6 +69,218 @@ impl DatabaseMessageHandler<PolymarketMessage> for PolymarketMessageHandler {
                 )
                 .await?;
 
+            // Full market JSON, used as the baseline for incremental syncs
+            client
+                .execute(
+                    "ALTER TABLE polymarket_markets ADD COLUMN IF NOT EXISTS raw JSONB",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_tokens (
+                        token_id VARCHAR(80) PRIMARY KEY,
+                        condition_id VARCHAR(66) NOT NULL
+                            REFERENCES polymarket_markets(condition_id) ON DELETE CASCADE,
+                        outcome TEXT NOT NULL,
+                        price DOUBLE PRECISION,
+                        winner BOOLEAN NOT NULL DEFAULT FALSE,
+                        updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                    )",
+                    &[],
+                )
+                .await?;
+
+            // Backfill tokens of markets stored before the table existed; prices and
+            // winners that do not cast cleanly are left empty rather than failing startup
+            client
+                .execute(
+                    "INSERT INTO polymarket_tokens (token_id, condition_id, outcome, price, winner)
+                     SELECT token->>'token_id', condition_id, COALESCE(token->>'outcome', ''),
+                            CASE WHEN btrim(token->>'price')
+                                ~ '^[-+]?([0-9]+[.]?[0-9]*|[.][0-9]+)([eE][-+]?[0-9]+)?$'
+                                THEN btrim(token->>'price')::DOUBLE PRECISION
+                            END,
+                            CASE WHEN jsonb_typeof(token->'winner') = 'boolean'
+                                THEN (token->'winner')::BOOLEAN
+                                ELSE FALSE
+                            END
+                     FROM polymarket_markets, jsonb_array_elements(
+                         CASE WHEN jsonb_typeof(tokens) = 'array' THEN tokens ELSE '[]'::jsonb END
+                     ) AS token
+                     WHERE COALESCE(token->>'token_id', '') <> ''
+                     ON CONFLICT (token_id) DO NOTHING",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_book_snapshots (
+                        id SERIAL PRIMARY KEY,
+                        asset_id VARCHAR(80) NOT NULL,
+                        market VARCHAR(66) NOT NULL,
+                        bids JSONB NOT NULL,
+                        asks JSONB NOT NULL,
+                        -- Empty when the book carried no hash, so such snapshots dedupe too
+                        hash VARCHAR(66) NOT NULL DEFAULT '',
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(asset_id, timestamp, hash)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .batch_execute(
+                    "DO $$
+                    BEGIN
+                        IF EXISTS (
+                            SELECT 1 FROM pg_constraint c
+                            JOIN pg_index i ON i.indexrelid = c.conindid
+                            WHERE c.conname = 'polymarket_book_snapshots_asset_id_timestamp_hash_key'
+                              AND NOT i.indnullsnotdistinct
+                        ) THEN
+                            DELETE FROM polymarket_book_snapshots a
+                                USING polymarket_book_snapshots b
+                                WHERE a.hash IS NULL AND b.hash IS NULL
+                                  AND a.asset_id = b.asset_id
+                                  AND a.timestamp = b.timestamp
+                                  AND a.id > b.id;
+                            ALTER TABLE polymarket_book_snapshots
+                                DROP CONSTRAINT polymarket_book_snapshots_asset_id_timestamp_hash_key,
+                                ADD CONSTRAINT polymarket_book_snapshots_asset_id_timestamp_hash_key
+                                    UNIQUE NULLS NOT DISTINCT (asset_id, timestamp, hash);
+                        END IF;
+                    END $$",
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_price_changes (
+                        id SERIAL PRIMARY KEY,
+                        asset_id VARCHAR(80) NOT NULL,
+                        market VARCHAR(66) NOT NULL,
+                        price DOUBLE PRECISION NOT NULL,
+                        size DOUBLE PRECISION NOT NULL,
+                        side VARCHAR(4) NOT NULL,
+                        best_bid DOUBLE PRECISION,
+                        best_ask DOUBLE PRECISION,
+                        hash VARCHAR(66) NOT NULL DEFAULT '',
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(asset_id, timestamp, side, price, hash)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_tick_size_changes (
+                        id SERIAL PRIMARY KEY,
+                        asset_id VARCHAR(80) NOT NULL,
+                        market VARCHAR(66) NOT NULL,
+                        old_tick_size DOUBLE PRECISION NOT NULL,
+                        new_tick_size DOUBLE PRECISION NOT NULL,
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(asset_id, timestamp)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_last_trades (
+                        id SERIAL PRIMARY KEY,
+                        asset_id VARCHAR(80) NOT NULL,
+                        market VARCHAR(66) NOT NULL,
+                        price DOUBLE PRECISION NOT NULL,
+                        size DOUBLE PRECISION NOT NULL,
+                        side VARCHAR(4) NOT NULL,
+                        fee_rate_bps DOUBLE PRECISION,
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(asset_id, timestamp, side, price, size)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_price_snapshots (
+                        id SERIAL PRIMARY KEY,
+                        token_id VARCHAR(80) NOT NULL,
+                        midpoint DOUBLE PRECISION,
+                        spread DOUBLE PRECISION,
+                        last_trade_price DOUBLE PRECISION,
+                        last_trade_side VARCHAR(4),
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(token_id, timestamp)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_price_history (
+                        id SERIAL PRIMARY KEY,
+                        token_id VARCHAR(80) NOT NULL,
+                        price DOUBLE PRECISION NOT NULL,
+                        timestamp TIMESTAMP NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(token_id, timestamp)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_market_events (
+                        id SERIAL PRIMARY KEY,
+                        condition_id VARCHAR(66) NOT NULL,
+                        event_type VARCHAR(16) NOT NULL,
+                        changes JSONB NOT NULL,
+                        detected_at TIMESTAMP NOT NULL,
+                        UNIQUE(condition_id, detected_at)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            // One row per field value; the current value has no valid_to
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS polymarket_market_history (
+                        id SERIAL PRIMARY KEY,
+                        condition_id VARCHAR(66) NOT NULL,
+                        field VARCHAR(64) NOT NULL,
+                        value TEXT,
+                        valid_from TIMESTAMP NOT NULL,
+                        valid_to TIMESTAMP
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE INDEX IF NOT EXISTS polymarket_market_history_current
+                     ON polymarket_market_history (condition_id, field)
+                     WHERE valid_to IS NULL",
+                    &[],
+                )
+                .await?;
+
             Ok(())
         })
     }
@@ -81,6 +298,41 @@ impl DatabaseMessageHandler<PolymarketMessage> for PolymarketMessageHandler {
                             error!(error = %e, "Failed to insert market");
                         }
                     }
+                    PolymarketMessage::Book(book) => {
+                        if let Err(e) = insert_book_snapshot(&client, book).await {
+                            error!(error = %e, "Failed to insert book snapshot");
+                        }
+                    }
+                    PolymarketMessage::PriceChange(change) => {
+                        if let Err(e) = insert_price_change(&client, change).await {
+                            error!(error = %e, "Failed to insert price change");
+                        }
+                    }
+                    PolymarketMessage::TickSizeChange(change) => {
+                        if let Err(e) = insert_tick_size_change(&client, change).await {
+                            error!(error = %e, "Failed to insert tick size change");
+                        }
+                    }
+                    PolymarketMessage::LastTrade(trade) => {
+                        if let Err(e) = insert_last_trade(&client, trade).await {
+                            error!(error = %e, "Failed to insert last trade");
+                        }
+                    }
+                    PolymarketMessage::PriceSnapshot(snapshot) => {
+                        if let Err(e) = insert_price_snapshot(&client, snapshot).await {
+                            error!(error = %e, "Failed to insert price snapshot");
+                        }
+                    }
+                    PolymarketMessage::PricePoint(point) => {
+                        if let Err(e) = insert_price_point(&client, point).await {
+                            error!(error = %e, "Failed to insert price point");
+                        }
+                    }
+                    PolymarketMessage::MarketEvent(event) => {
+                        if let Err(e) = insert_market_event(&client, event).await {
+                            error!(error = %e, "Failed to insert market event");
+                        }
+                    }
                 }
             }
             Ok(())
@@ -97,6 +349,64 @@ fn parse_timestamp(value: Option<&str>) -> Option<NaiveDateTime> {
     })
 }
 
+/// Convert an exchange timestamp in milliseconds to NaiveDateTime
+fn millis_to_timestamp(millis: i64) -> Option<NaiveDateTime> {
+    chrono::DateTime::from_timestamp_millis(millis).map(|dt| dt.naive_utc())
+}
+
+/// Loads outcome token IDs of stored markets.
+///
+/// With `active_only`, only tokens of markets that are active and not closed are returned.
+pub async fn load_token_ids(
+    client: &Client,
+    active_only: bool,
+) -> Result<Vec<String>, tokio_postgres::Error> {
+    let rows = client
+        .query(
+            "SELECT t.token_id
+             FROM polymarket_tokens t
+             JOIN polymarket_markets m ON m.condition_id = t.condition_id
+             WHERE NOT $1 OR (m.active AND NOT m.closed)
+             ORDER BY t.token_id",
+            &[&active_only],
+        )
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get("token_id")).collect())
+}
+
+/// Loads the last stored state of every market, used to seed an incremental sync.
+///
+/// Markets stored before the `raw` column existed are rebuilt from their columns.
+pub async fn load_markets(client: &Client) -> Result<Vec<Market>> {
+    let rows = client
+        .query(
+            "SELECT COALESCE(raw, to_jsonb(m) - 'id' - 'raw' - 'received_at') AS market
+             FROM polymarket_markets m",
+            &[],
+        )
+        .await?;
+
+    let mut markets = Vec::with_capacity(rows.len());
+    for row in rows {
+        let value: serde_json::Value = row.get("market");
+        match serde_json::from_value::<Market>(value) {
+            Ok(market) => markets.push(market),
+            Err(e) => error!(error = %e, "Failed to decode stored market, skipping"),
+        }
+    }
+    Ok(markets)
+}
+
+/// Re-reads the stored markets before every round of
+/// [`PolymarketMarketSync`](crate::connectors::polymarket::PolymarketMarketSync).
+pub fn market_loader(client: Arc<Client>) -> BaselineLoader {
+    Box::new(move || {
+        let client = Arc::clone(&client);
+        Box::pin(async move { load_markets(&client).await })
+    })
+}
+
 async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_postgres::Error> {
     let end_date_iso = parse_timestamp(market.end_date_iso.as_deref());
     let game_start_time = parse_timestamp(market.game_start_time.as_deref());
@@ -117,7 +427,7 @@ async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_post
                 minimum_order_size, minimum_tick_size, maker_base_fee, taker_base_fee, seconds_delay,
                 tokens, rewards, tags,
                 icon, image, fpmm, neg_risk_market_id, neg_risk_request_id,
-                notifications_enabled, is_50_50_outcome
+                notifications_enabled, is_50_50_outcome, raw
             ) VALUES (
                 $1, $2, $3, $4, $5,
                 $6, $7, $8, $9, $10, $11,
@@ -125,7 +435,7 @@ async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_post
                 $15, $16, $17, $18, $19,
                 $20, $21, $22,
                 $23, $24, $25, $26, $27,
-                $28, $29
+                $28, $29, $30
             )
             ON CONFLICT (condition_id) DO UPDATE SET
                 question_id = EXCLUDED.question_id,
@@ -156,6 +466,7 @@ async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_post
                 neg_risk_request_id = EXCLUDED.neg_risk_request_id,
                 notifications_enabled = EXCLUDED.notifications_enabled,
                 is_50_50_outcome = EXCLUDED.is_50_50_outcome,
+                raw = EXCLUDED.raw,
                 received_at = CURRENT_TIMESTAMP",
             &[
                 &market.condition_id,
@@ -177,9 +488,9 @@ async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_post
                 &market.maker_base_fee,
                 &market.taker_base_fee,
                 &market.seconds_delay,
-                &market.tokens,
-                &market.rewards,
-                &market.tags,
+                &Json(&market.tokens),
+                &Json(&market.rewards),
+                &Json(&market.tags),
                 &market.icon,
                 &market.image,
                 &market.fpmm,
@@ -187,9 +498,243 @@ async fn insert_market(client: &Client, market: Market) -> Result<(), tokio_post
                 &market.neg_risk_request_id,
                 &market.notifications_enabled,
                 &market.is_50_50_outcome,
+                &Json(&market),
+            ],
+        )
+        .await?;
+
+    for token in &market.tokens {
+        if token.token_id.is_empty() {
+            continue;
+        }
+        client
+            .execute(
+                "INSERT INTO polymarket_tokens (token_id, condition_id, outcome, price, winner)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (token_id) DO UPDATE SET
+                    condition_id = EXCLUDED.condition_id,
+                    outcome = EXCLUDED.outcome,
+                    price = EXCLUDED.price,
+                    winner = EXCLUDED.winner,
+                    updated_at = CURRENT_TIMESTAMP",
+                &[
+                    &token.token_id,
+                    &market.condition_id,
+                    &token.outcome,
+                    &token.price,
+                    &token.winner,
+                ],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_book_snapshot(
+    client: &Client,
+    book: BookSnapshot,
+) -> Result<(), tokio_postgres::Error> {
+    let timestamp = millis_to_timestamp(book.timestamp);
+    let bids = serde_json::to_value(&book.bids).unwrap_or_default();
+    let asks = serde_json::to_value(&book.asks).unwrap_or_default();
+
+    client
+        .execute(
+            "INSERT INTO polymarket_book_snapshots (asset_id, market, bids, asks, hash, timestamp)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (asset_id, timestamp, hash) DO NOTHING",
+            &[
+                &book.asset_id,
+                &book.market,
+                &bids,
+                &asks,
+                &book.hash.unwrap_or_default(),
+                &timestamp,
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn insert_price_change(
+    client: &Client,
+    change: PriceChange,
+) -> Result<(), tokio_postgres::Error> {
+    let timestamp = millis_to_timestamp(change.timestamp);
+
+    for level in change.price_changes {
+        client
+            .execute(
+                "INSERT INTO polymarket_price_changes
+                    (asset_id, market, price, size, side, best_bid, best_ask, hash, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (asset_id, timestamp, side, price, hash) DO NOTHING",
+                &[
+                    &level.asset_id,
+                    &change.market,
+                    &level.price,
+                    &level.size,
+                    &level.side,
+                    &level.best_bid,
+                    &level.best_ask,
+                    &level.hash.unwrap_or_default(),
+                    &timestamp,
+                ],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_tick_size_change(
+    client: &Client,
+    change: TickSizeChange,
+) -> Result<(), tokio_postgres::Error> {
+    let timestamp = millis_to_timestamp(change.timestamp);
+
+    client
+        .execute(
+            "INSERT INTO polymarket_tick_size_changes
+                (asset_id, market, old_tick_size, new_tick_size, timestamp)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (asset_id, timestamp) DO NOTHING",
+            &[
+                &change.asset_id,
+                &change.market,
+                &change.old_tick_size,
+                &change.new_tick_size,
+                &timestamp,
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn insert_last_trade(client: &Client, trade: LastTrade) -> Result<(), tokio_postgres::Error> {
+    let timestamp = millis_to_timestamp(trade.timestamp);
+
+    client
+        .execute(
+            "INSERT INTO polymarket_last_trades
+                (asset_id, market, price, size, side, fee_rate_bps, timestamp)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (asset_id, timestamp, side, price, size) DO NOTHING",
+            &[
+                &trade.asset_id,
+                &trade.market,
+                &trade.price,
+                &trade.size,
+                &trade.side,
+                &trade.fee_rate_bps,
+                &timestamp,
             ],
         )
         .await?;
 
     Ok(())
 }
+
+async fn insert_price_snapshot(
+    client: &Client,
+    snapshot: PriceSnapshot,
+) -> Result<(), tokio_postgres::Error> {
+    let timestamp = millis_to_timestamp(snapshot.timestamp);
+
+    client
+        .execute(
+            "INSERT INTO polymarket_price_snapshots
+                (token_id, midpoint, spread, last_trade_price, last_trade_side, timestamp)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (token_id, timestamp) DO NOTHING",
+            &[
+                &snapshot.token_id,
+                &snapshot.midpoint,
+                &snapshot.spread,
+                &snapshot.last_trade_price,
+                &snapshot.last_trade_side,
+                &timestamp,
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn insert_price_point(
+    client: &Client,
+    point: PricePoint,
+) -> Result<(), tokio_postgres::Error> {
+    // Price history samples are timestamped in seconds
+    let timestamp = chrono::DateTime::from_timestamp(point.timestamp, 0).map(|dt| dt.naive_utc());
+
+    client
+        .execute(
+            "INSERT INTO polymarket_price_history (token_id, price, timestamp)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (token_id, timestamp) DO NOTHING",
+            &[&point.token_id, &point.price, &timestamp],
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn insert_market_event(
+    client: &Client,
+    event: MarketEvent,
+) -> Result<(), tokio_postgres::Error> {
+    let detected_at = millis_to_timestamp(event.detected_at);
+
+    let inserted = client
+        .execute(
+            "INSERT INTO polymarket_market_events (condition_id, event_type, changes, detected_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (condition_id, detected_at) DO NOTHING",
+            &[
+                &event.condition_id,
+                &event.kind.as_str(),
+                &Json(&event.changes),
+                &detected_at,
+            ],
+        )
+        .await?;
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    info!(
+        condition_id = %event.condition_id,
+        kind = event.kind.as_str(),
+        changes = event.changes.len(),
+        "Recorded polymarket market event"
+    );
+
+    // Close the current value of each changed field and open the new one
+    for change in &event.changes {
+        client
+            .execute(
+                "UPDATE polymarket_market_history SET valid_to = $3
+                 WHERE condition_id = $1 AND field = $2 AND valid_to IS NULL",
+                &[&event.condition_id, &change.field, &detected_at],
+            )
+            .await?;
+        client
+            .execute(
+                "INSERT INTO polymarket_market_history (condition_id, field, value, valid_from)
+                 VALUES ($1, $2, $3, $4)",
+                &[
+                    &event.condition_id,
+                    &change.field,
+                    &change.new_value,
+                    &detected_at,
+                ],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
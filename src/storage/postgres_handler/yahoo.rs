@@ -1,38 +1,111 @@
 //! PostgreSQL handler for YahooMessage.
 
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::connectors::yahoo::types::{CalendarDateType, YahooMessage};
-use crate::storage::postgres::DatabaseMessageHandler;
+use crate::connectors::yahoo::types::{
+    BalanceSheetRow, CalendarDateType, CashflowRow, Dividend, IncomeStatementRow, PriceBar, Split,
+    StatementFrequency, YahooMessage,
+};
+use crate::core::MessageOutcome;
+use crate::storage::postgres::{BatchOutcome, DatabaseMessageHandler, InsertOutcome};
 use anyhow::Result;
+use chrono::NaiveDate;
 use paft_domain::period::Period;
 use paft_money::money::Money;
+use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
+use serde_json::Value;
 use tokio_postgres::Client;
 
 pub struct YahooMessageHandler;
 
+/// Statements with a wide table and line items that get trailing-twelve-month rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TtmTable {
+    IncomeStatement,
+    BalanceSheet,
+    Cashflow,
+}
+
+impl TtmTable {
+    /// Name of the wide table.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TtmTable::IncomeStatement => "income_statements",
+            TtmTable::BalanceSheet => "balance_sheets",
+            TtmTable::Cashflow => "cashflow_statements",
+        }
+    }
+
+    /// Statement name in `financial_line_items`.
+    fn statement(&self) -> &'static str {
+        match self {
+            TtmTable::IncomeStatement => "income_statement",
+            TtmTable::BalanceSheet => "balance_sheet",
+            TtmTable::Cashflow => "cashflow",
+        }
+    }
+
+    fn columns(&self) -> &'static [&'static str] {
+        match self {
+            TtmTable::IncomeStatement => &[
+                "total_revenue",
+                "gross_profit",
+                "operating_income",
+                "net_income",
+            ],
+            TtmTable::BalanceSheet => &[
+                "total_assets",
+                "total_liabilities",
+                "total_equity",
+                "cash",
+                "long_term_debt",
+                "shares_outstanding",
+            ],
+            TtmTable::Cashflow => &[
+                "operating_cashflow",
+                "capital_expenditures",
+                "free_cash_flow",
+                "net_income",
+            ],
+        }
+    }
+}
+
 impl DatabaseMessageHandler<YahooMessage> for YahooMessageHandler {
     fn initialize_schema(
         &self,
         client: Arc<Client>,
     ) -> Pin<Box<dyn Future<Output = Result<(), tokio_postgres::Error>> + Send>> {
         Box::pin(async move {
+            // Earlier versions only stored quarterly statements in quarterly_* tables
+            for (old, new) in [
+                ("quarterly_income_statements", "income_statements"),
+                ("quarterly_balance_sheets", "balance_sheets"),
+                ("quarterly_cashflow_statements", "cashflow_statements"),
+            ] {
+                Self::migrate_quarterly_table(&client, old, new).await?;
+            }
+
             // Create tables for Yahoo finance data
             client
                 .execute(
-                    "CREATE TABLE IF NOT EXISTS quarterly_income_statements (
+                    "CREATE TABLE IF NOT EXISTS income_statements (
                         id SERIAL PRIMARY KEY,
                         symbol VARCHAR(10) NOT NULL,
-                        period_date DATE,                     
+                        frequency VARCHAR(10) NOT NULL DEFAULT 'quarterly' CHECK (frequency IN ('quarterly', 'annual', 'ttm')),
+                        period_date DATE,
                         total_revenue DOUBLE PRECISION,
                         gross_profit DOUBLE PRECISION,
                         operating_income DOUBLE PRECISION,
                         net_income DOUBLE PRECISION,
                         received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                        UNIQUE(symbol, period_date)
+                        UNIQUE(symbol, frequency, period_date)
                     )",
                     &[],
                 )
@@ -40,10 +113,11 @@ impl DatabaseMessageHandler<YahooMessage> for YahooMessageHandler {
 
             client
                 .execute(
-                    "CREATE TABLE IF NOT EXISTS quarterly_balance_sheets (
+                    "CREATE TABLE IF NOT EXISTS balance_sheets (
                         id SERIAL PRIMARY KEY,
                         symbol VARCHAR(10) NOT NULL,
-                        period_date DATE,                     
+                        frequency VARCHAR(10) NOT NULL DEFAULT 'quarterly' CHECK (frequency IN ('quarterly', 'annual', 'ttm')),
+                        period_date DATE,
                         total_assets DOUBLE PRECISION,
                         total_liabilities DOUBLE PRECISION,
                         total_equity DOUBLE PRECISION,
@@ -51,7 +125,7 @@ impl DatabaseMessageHandler<YahooMessage> for YahooMessageHandler {
                         long_term_debt DOUBLE PRECISION,
                         shares_outstanding BIGINT,
                         received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                        UNIQUE(symbol, period_date)
+                        UNIQUE(symbol, frequency, period_date)
                     )",
                     &[],
                 )
@@ -59,21 +133,109 @@ impl DatabaseMessageHandler<YahooMessage> for YahooMessageHandler {
 
             client
                 .execute(
-                    "CREATE TABLE IF NOT EXISTS quarterly_cashflow_statements (
+                    "CREATE TABLE IF NOT EXISTS cashflow_statements (
                         id SERIAL PRIMARY KEY,
                         symbol VARCHAR(10) NOT NULL,
-                        period_date DATE,                     
+                        frequency VARCHAR(10) NOT NULL DEFAULT 'quarterly' CHECK (frequency IN ('quarterly', 'annual', 'ttm')),
+                        period_date DATE,
                         operating_cashflow DOUBLE PRECISION,
                         capital_expenditures DOUBLE PRECISION,
                         free_cash_flow DOUBLE PRECISION,
                         net_income DOUBLE PRECISION,
                         received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                        UNIQUE(symbol, period_date)
+                        UNIQUE(symbol, frequency, period_date)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            // Every numeric line item of each statement, so new items need no schema change
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS financial_line_items (
+                        id BIGSERIAL PRIMARY KEY,
+                        symbol VARCHAR(10) NOT NULL,
+                        frequency VARCHAR(10) NOT NULL CHECK (frequency IN ('quarterly', 'annual', 'ttm')),
+                        period_date DATE NOT NULL,
+                        statement VARCHAR(24) NOT NULL,
+                        item VARCHAR(128) NOT NULL,
+                        value NUMERIC NOT NULL,
+                        currency VARCHAR(8),
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(symbol, frequency, period_date, statement, item)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS price_bars (
+                        id SERIAL PRIMARY KEY,
+                        symbol VARCHAR(10) NOT NULL,
+                        bar_interval VARCHAR(4) NOT NULL,
+                        ts TIMESTAMP NOT NULL,
+                        open DOUBLE PRECISION NOT NULL,
+                        high DOUBLE PRECISION NOT NULL,
+                        low DOUBLE PRECISION NOT NULL,
+                        close DOUBLE PRECISION NOT NULL,
+                        volume BIGINT,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(symbol, bar_interval, ts)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS dividends (
+                        id SERIAL PRIMARY KEY,
+                        symbol VARCHAR(10) NOT NULL,
+                        ex_date TIMESTAMP NOT NULL,
+                        amount DOUBLE PRECISION NOT NULL,
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(symbol, ex_date)
+                    )",
+                    &[],
+                )
+                .await?;
+
+            client
+                .execute(
+                    "CREATE TABLE IF NOT EXISTS splits (
+                        id SERIAL PRIMARY KEY,
+                        symbol VARCHAR(10) NOT NULL,
+                        split_date TIMESTAMP NOT NULL,
+                        numerator INTEGER NOT NULL CHECK (numerator > 0),
+                        denominator INTEGER NOT NULL CHECK (denominator > 0),
+                        received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        UNIQUE(symbol, split_date)
                     )",
                     &[],
                 )
                 .await?;
 
+            // Bars divided by the ratio of every later split, comparable across splits
+            client
+                .execute(
+                    "CREATE OR REPLACE VIEW split_adjusted_price_bars AS
+                    SELECT b.symbol, b.bar_interval, b.ts,
+                        b.open / f.factor AS open,
+                        b.high / f.factor AS high,
+                        b.low / f.factor AS low,
+                        b.close / f.factor AS close,
+                        ROUND(b.volume * f.factor)::BIGINT AS volume
+                    FROM price_bars b
+                    CROSS JOIN LATERAL (
+                        SELECT COALESCE(EXP(SUM(LN(s.numerator::DOUBLE PRECISION / s.denominator))), 1) AS factor
+                        FROM splits s
+                        WHERE s.symbol = b.symbol AND s.split_date > b.ts
+                    ) f",
+                    &[],
+                )
+                .await?;
+
             client
                 .execute(
                     "CREATE TABLE IF NOT EXISTS calendar_dates (
@@ -98,53 +260,372 @@ impl DatabaseMessageHandler<YahooMessage> for YahooMessageHandler {
         batch: Vec<YahooMessage>,
     ) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
         Box::pin(async move {
-            for message in batch {
-                match message {
-                    YahooMessage::IncomeStatement(row) => {
-                        tracing::info!(
-                            symbol = %row.symbol,
-                            data_type = "income_statement",
-                            "Inserting financial data"
-                        );
-                        Self::insert_income_statement(&client, row).await;
-                    }
-                    YahooMessage::BalanceSheet(row) => {
-                        tracing::info!(
-                            symbol = %row.symbol,
-                            data_type = "balance_sheet",
-                            "Inserting financial data"
-                        );
-                        Self::insert_balance_sheet(&client, row).await;
+            Self::insert_messages(&client, batch).await.into_result()?;
+            Ok(())
+        })
+    }
+
+    fn insert_batch_outcomes(
+        &self,
+        client: Arc<Client>,
+        batch: Vec<YahooMessage>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<MessageOutcome>>> + Send>> {
+        Box::pin(async move { Ok(Self::insert_messages(&client, batch).await.messages) })
+    }
+}
+
+impl YahooMessageHandler {
+    /// Inserts every message, recording one outcome per message; a failed TTM row rejects
+    /// the messages it was derived from.
+    async fn insert_messages(client: &Client, batch: Vec<YahooMessage>) -> BatchOutcome {
+        let mut outcome = BatchOutcome::default();
+        // Trailing-twelve-month rows to derive once their quarters are stored, with the
+        // indices of the messages they are derived from
+        let mut ttm: BTreeMap<_, Vec<usize>> = BTreeMap::new();
+
+        for (idx, message) in batch.into_iter().enumerate() {
+            let row_outcome = match message {
+                YahooMessage::IncomeStatement(row) => {
+                    tracing::info!(
+                        symbol = %row.symbol,
+                        data_type = "income_statement",
+                        frequency = row.frequency.as_str(),
+                        "Inserting financial data"
+                    );
+                    if row.frequency == StatementFrequency::Ttm
+                        && let Some(period_date) = Self::extract_period_date(&row.inner.period)
+                    {
+                        ttm.entry((TtmTable::IncomeStatement, row.symbol.clone(), period_date))
+                            .or_default()
+                            .push(idx);
                     }
-                    YahooMessage::Cashflow(row) => {
-                        tracing::info!(
-                            symbol = %row.symbol,
-                            data_type = "cashflow",
-                            "Inserting financial data"
-                        );
-                        Self::insert_cashflow(&client, row).await;
+                    Self::insert_income_statement(client, &row).await
+                }
+                YahooMessage::BalanceSheet(row) => {
+                    tracing::info!(
+                        symbol = %row.symbol,
+                        data_type = "balance_sheet",
+                        frequency = row.frequency.as_str(),
+                        "Inserting financial data"
+                    );
+                    if row.frequency == StatementFrequency::Ttm
+                        && let Some(period_date) = Self::extract_period_date(&row.inner.period)
+                    {
+                        ttm.entry((TtmTable::BalanceSheet, row.symbol.clone(), period_date))
+                            .or_default()
+                            .push(idx);
                     }
-                    YahooMessage::Calendar(_cal) => {
-                        tracing::info!(
-                            symbol = %_cal.symbol,
-                            data_type = "calendar",
-                            "Inserting financial data"
-                        );
-                        Self::insert_calendar(&client, _cal).await;
+                    Self::insert_balance_sheet(client, &row).await
+                }
+                YahooMessage::Cashflow(row) => {
+                    tracing::info!(
+                        symbol = %row.symbol,
+                        data_type = "cashflow",
+                        frequency = row.frequency.as_str(),
+                        "Inserting financial data"
+                    );
+                    if row.frequency == StatementFrequency::Ttm
+                        && let Some(period_date) = Self::extract_period_date(&row.inner.period)
+                    {
+                        ttm.entry((TtmTable::Cashflow, row.symbol.clone(), period_date))
+                            .or_default()
+                            .push(idx);
                     }
+                    Self::insert_cashflow(client, &row).await
+                }
+                YahooMessage::PriceBar(bar) => {
+                    tracing::debug!(
+                        symbol = %bar.symbol,
+                        data_type = "price_bar",
+                        "Inserting financial data"
+                    );
+                    Self::insert_price_bar(client, bar).await
+                }
+                YahooMessage::Dividend(dividend) => {
+                    tracing::info!(
+                        symbol = %dividend.symbol,
+                        data_type = "dividend",
+                        "Inserting financial data"
+                    );
+                    Self::insert_dividend(client, dividend).await
+                }
+                YahooMessage::Split(split) => {
+                    tracing::info!(
+                        symbol = %split.symbol,
+                        data_type = "split",
+                        "Inserting financial data"
+                    );
+                    Self::insert_split(client, split).await
+                }
+                YahooMessage::Calendar(_cal) => {
+                    tracing::info!(
+                        symbol = %_cal.symbol,
+                        data_type = "calendar",
+                        "Inserting financial data"
+                    );
+                    Self::insert_calendar(client, _cal).await
+                }
+            };
+            outcome.record(row_outcome);
+        }
+
+        // Derived rows only count when they fail; a TTM window may not be complete yet
+        for ((table, symbol, period_date), sources) in ttm {
+            for derived in [
+                Self::insert_ttm(client, table, &symbol, period_date).await,
+                Self::insert_ttm_line_items(client, table, &symbol, period_date).await,
+            ] {
+                if let InsertOutcome::Failed(e) = derived {
+                    outcome.record_derived_error(e, &sources);
                 }
             }
-            Ok(())
-        })
+        }
+
+        tracing::info!(
+            inserted = outcome.inserted,
+            duplicates = outcome.duplicates,
+            skipped = outcome.skipped,
+            failed = outcome.failed,
+            "Inserted Yahoo batch"
+        );
+        outcome
     }
-}
 
-impl YahooMessageHandler {
     /// Extract a Decimal amount to f64, returning None if missing
     fn extract_amount(amount: Option<&Money>) -> Option<f64> {
         amount?.amount().to_f64()
     }
 
+    /// Frequency a row is stored under; TTM rows arrive as the quarters they roll up.
+    fn stored_frequency(frequency: StatementFrequency) -> &'static str {
+        match frequency {
+            StatementFrequency::Ttm => StatementFrequency::Quarterly.as_str(),
+            frequency => frequency.as_str(),
+        }
+    }
+
+    /// Renames a pre-frequency `quarterly_*` table and keys its rows by frequency, with
+    /// the same frequency check as freshly created tables.
+    async fn migrate_quarterly_table(
+        client: &Client,
+        old: &str,
+        new: &str,
+    ) -> Result<(), tokio_postgres::Error> {
+        client
+            .batch_execute(&format!(
+                "DO $$
+                BEGIN
+                    IF to_regclass('{old}') IS NOT NULL AND to_regclass('{new}') IS NULL THEN
+                        ALTER TABLE {old} RENAME TO {new};
+                        ALTER TABLE {new}
+                            ADD COLUMN frequency VARCHAR(10) NOT NULL DEFAULT 'quarterly'
+                                CHECK (frequency IN ('quarterly', 'annual', 'ttm')),
+                            DROP CONSTRAINT IF EXISTS {old}_symbol_period_date_key,
+                            ADD CONSTRAINT {new}_symbol_frequency_period_date_key
+                                UNIQUE (symbol, frequency, period_date);
+                    END IF;
+                    -- Tables migrated by earlier versions lack the frequency check
+                    IF to_regclass('{new}') IS NOT NULL AND NOT EXISTS (
+                        SELECT 1 FROM pg_constraint
+                        WHERE conrelid = to_regclass('{new}') AND conname = '{new}_frequency_check'
+                    ) THEN
+                        ALTER TABLE {new} ADD CONSTRAINT {new}_frequency_check
+                            CHECK (frequency IN ('quarterly', 'annual', 'ttm'));
+                    END IF;
+                END $$"
+            ))
+            .await
+    }
+
+    /// Derives the `ttm` row ending at `period_date` from the stored quarters.
+    ///
+    /// Flows (income and cashflow) sum the four quarters ending at `period_date` and are
+    /// only written once all four are present and consecutive (spanning under 11 months);
+    /// balance sheets are point-in-time, so the TTM row is the quarter itself.
+    pub async fn insert_ttm(
+        client: &Client,
+        table: TtmTable,
+        symbol: &str,
+        period_date: NaiveDate,
+    ) -> InsertOutcome {
+        let name = table.name();
+        let columns = table.columns();
+        let updates = columns
+            .iter()
+            .map(|column| format!("{column} = EXCLUDED.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let source = match table {
+            TtmTable::BalanceSheet => format!(
+                "SELECT $1::varchar, 'ttm', $2::date, {}
+                FROM {name}
+                WHERE symbol = $1 AND frequency = 'quarterly' AND period_date = $2::date",
+                columns.join(", ")
+            ),
+            TtmTable::IncomeStatement | TtmTable::Cashflow => format!(
+                "SELECT $1::varchar, 'ttm', $2::date, {}
+                FROM (
+                    SELECT * FROM {name}
+                    WHERE symbol = $1 AND frequency = 'quarterly' AND period_date <= $2::date
+                    ORDER BY period_date DESC
+                    LIMIT 4
+                ) quarters
+                HAVING COUNT(*) = 4
+                    AND MAX(period_date) = $2::date
+                    AND MIN(period_date) > $2::date - INTERVAL '11 months'",
+                columns
+                    .iter()
+                    .map(|column| format!("CASE WHEN COUNT({column}) = 4 THEN SUM({column}) END"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    &format!(
+                        "INSERT INTO {name} (symbol, frequency, period_date, {})
+                        {source}
+                        ON CONFLICT (symbol, frequency, period_date) DO UPDATE SET {updates}",
+                        columns.join(", ")
+                    ),
+                    &[&symbol, &period_date],
+                )
+                .await,
+            &format!("TTM {} for {} ({})", name, symbol, period_date),
+        )
+    }
+
+    /// Upserts every numeric line item of a statement row; restated values replace old ones.
+    async fn insert_line_items<T: Serialize>(
+        client: &Client,
+        table: TtmTable,
+        symbol: &str,
+        frequency: &str,
+        period_date: NaiveDate,
+        row: &T,
+    ) -> InsertOutcome {
+        let items = match serde_json::to_value(row) {
+            Ok(value) => line_items(&value),
+            Err(e) => {
+                return InsertOutcome::Failed(format!(
+                    "{} line items for {} ({}): {}",
+                    table.statement(),
+                    symbol,
+                    period_date,
+                    e
+                ));
+            }
+        };
+        if items.is_empty() {
+            return InsertOutcome::Skipped(format!(
+                "{} line items for {} ({}): no numeric fields",
+                table.statement(),
+                symbol,
+                period_date
+            ));
+        }
+
+        let mut names = Vec::with_capacity(items.len());
+        let mut values = Vec::with_capacity(items.len());
+        let mut currencies = Vec::with_capacity(items.len());
+        for item in items {
+            names.push(item.item);
+            values.push(item.value.to_string());
+            currencies.push(item.currency);
+        }
+
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO financial_line_items
+                        (symbol, frequency, period_date, statement, item, value, currency)
+                    SELECT $1, $2, $3, $4, item, value::NUMERIC, currency
+                    FROM UNNEST($5::text[], $6::text[], $7::text[]) AS items(item, value, currency)
+                    ON CONFLICT (symbol, frequency, period_date, statement, item) DO UPDATE SET
+                        value = EXCLUDED.value,
+                        currency = EXCLUDED.currency,
+                        received_at = CURRENT_TIMESTAMP",
+                    &[
+                        &symbol,
+                        &frequency,
+                        &period_date,
+                        &table.statement(),
+                        &names,
+                        &values,
+                        &currencies,
+                    ],
+                )
+                .await,
+            &format!(
+                "{} line items for {} ({})",
+                table.statement(),
+                symbol,
+                period_date
+            ),
+        )
+    }
+
+    /// Derives `ttm` line items the same way as the wide `ttm` rows.
+    pub async fn insert_ttm_line_items(
+        client: &Client,
+        table: TtmTable,
+        symbol: &str,
+        period_date: NaiveDate,
+    ) -> InsertOutcome {
+        let source = match table {
+            TtmTable::BalanceSheet => {
+                "SELECT $1::varchar, 'ttm', $2::date, $3::varchar, item, value, currency
+                FROM financial_line_items
+                WHERE symbol = $1 AND frequency = 'quarterly' AND statement = $3
+                    AND period_date = $2::date"
+            }
+            TtmTable::IncomeStatement | TtmTable::Cashflow => {
+                "SELECT $1::varchar, 'ttm', $2::date, $3::varchar, item, SUM(value), MIN(currency)
+                FROM financial_line_items
+                WHERE symbol = $1 AND frequency = 'quarterly' AND statement = $3
+                    AND period_date IN (
+                        SELECT DISTINCT period_date FROM financial_line_items
+                        WHERE symbol = $1 AND frequency = 'quarterly' AND statement = $3
+                            AND period_date <= $2::date
+                        ORDER BY period_date DESC
+                        LIMIT 4
+                    )
+                GROUP BY item
+                HAVING COUNT(*) = 4
+                    AND MAX(period_date) = $2::date
+                    AND MIN(period_date) > $2::date - INTERVAL '11 months'
+                    AND COUNT(DISTINCT currency) <= 1
+                    AND COUNT(currency) IN (0, 4)"
+            }
+        };
+
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    &format!(
+                        "INSERT INTO financial_line_items
+                            (symbol, frequency, period_date, statement, item, value, currency)
+                        {source}
+                        ON CONFLICT (symbol, frequency, period_date, statement, item) DO UPDATE SET
+                            value = EXCLUDED.value,
+                            currency = EXCLUDED.currency,
+                            received_at = CURRENT_TIMESTAMP"
+                    ),
+                    &[&symbol, &period_date, &table.statement()],
+                )
+                .await,
+            &format!(
+                "TTM {} line items for {} ({})",
+                table.statement(),
+                symbol,
+                period_date
+            ),
+        )
+    }
+
     /// Extract period date from Period enum
     fn extract_period_date(period: &Period) -> Option<chrono::NaiveDate> {
         match period {
@@ -153,29 +634,37 @@ impl YahooMessageHandler {
         }
     }
 
-    async fn insert_income_statement(
-        client: &Client,
-        row: crate::connectors::yahoo::types::IncomeStatementRow,
-    ) {
+    async fn insert_income_statement(client: &Client, row: &IncomeStatementRow) -> InsertOutcome {
+        let frequency = Self::stored_frequency(row.frequency);
         let period_date = match Self::extract_period_date(&row.inner.period) {
             Some(date) => date,
             None => {
-                tracing::warn!(
-                    "Skipping income statement for {}: invalid period",
-                    row.symbol
-                );
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "income statement for {}: period {:?} is not a date",
+                    row.symbol, row.inner.period
+                ));
             }
         };
+        let line_items = Self::insert_line_items(
+            client,
+            TtmTable::IncomeStatement,
+            &row.symbol,
+            frequency,
+            period_date,
+            &row.inner,
+        )
+        .await;
+        if line_items.is_failed() {
+            return line_items;
+        }
 
         let total_revenue = match Self::extract_amount(row.inner.total_revenue.as_ref()) {
             Some(val) => val,
             None => {
-                tracing::warn!(
-                    "Skipping income statement for {}: missing total_revenue",
-                    row.symbol
-                );
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "income statement for {} ({}): missing total_revenue",
+                    row.symbol, period_date
+                ));
             }
         };
 
@@ -183,52 +672,64 @@ impl YahooMessageHandler {
         let operating_income = Self::extract_amount(row.inner.operating_income.as_ref());
         let net_income = Self::extract_amount(row.inner.net_income.as_ref());
 
-        if let Err(e) = client
-            .execute(
-                "INSERT INTO quarterly_income_statements 
-                    (symbol, period_date, total_revenue, gross_profit, operating_income, net_income)
-                VALUES ($1, $2, $3, $4, $5, $6)
-                ON CONFLICT (symbol, period_date) DO NOTHING",
-                &[
-                    &row.symbol,
-                    &period_date,
-                    &total_revenue,
-                    &gross_profit,
-                    &operating_income,
-                    &net_income,
-                ],
-            )
-            .await
-        {
-            tracing::error!(
-                "Failed to insert income statement for {} ({}): {}",
-                row.symbol,
-                period_date,
-                e
-            );
-        }
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO income_statements
+                        (symbol, frequency, period_date, total_revenue, gross_profit, operating_income, net_income)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    ON CONFLICT (symbol, frequency, period_date) DO UPDATE SET
+                        total_revenue = EXCLUDED.total_revenue,
+                        gross_profit = EXCLUDED.gross_profit,
+                        operating_income = EXCLUDED.operating_income,
+                        net_income = EXCLUDED.net_income,
+                        received_at = CURRENT_TIMESTAMP",
+                    &[
+                        &row.symbol,
+                        &frequency,
+                        &period_date,
+                        &total_revenue,
+                        &gross_profit,
+                        &operating_income,
+                        &net_income,
+                    ],
+                )
+                .await,
+            &format!("income statement for {} ({})", row.symbol, period_date),
+        )
     }
 
-    async fn insert_balance_sheet(
-        client: &Client,
-        row: crate::connectors::yahoo::types::BalanceSheetRow,
-    ) {
+    async fn insert_balance_sheet(client: &Client, row: &BalanceSheetRow) -> InsertOutcome {
+        let frequency = Self::stored_frequency(row.frequency);
         let period_date = match Self::extract_period_date(&row.inner.period) {
             Some(date) => date,
             None => {
-                tracing::warn!("Skipping balance sheet for {}: invalid period", row.symbol);
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "balance sheet for {}: period {:?} is not a date",
+                    row.symbol, row.inner.period
+                ));
             }
         };
+        let line_items = Self::insert_line_items(
+            client,
+            TtmTable::BalanceSheet,
+            &row.symbol,
+            frequency,
+            period_date,
+            &row.inner,
+        )
+        .await;
+        if line_items.is_failed() {
+            return line_items;
+        }
 
         let total_assets = match Self::extract_amount(row.inner.total_assets.as_ref()) {
             Some(val) => val,
             None => {
-                tracing::warn!(
-                    "Skipping balance sheet for {}: missing total_assets",
-                    row.symbol
-                );
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "balance sheet for {} ({}): missing total_assets",
+                    row.symbol, period_date
+                ));
             }
         };
 
@@ -238,51 +739,68 @@ impl YahooMessageHandler {
         let long_term_debt = Self::extract_amount(row.inner.long_term_debt.as_ref());
         let shares_outstanding = row.inner.shares_outstanding;
 
-        if let Err(e) = client
-            .execute(
-                "INSERT INTO quarterly_balance_sheets 
-                    (symbol, period_date, total_assets, total_liabilities, total_equity, cash, long_term_debt, shares_outstanding)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-                ON CONFLICT (symbol, period_date) DO NOTHING",
-                &[
-                    &row.symbol,
-                    &period_date,
-                    &total_assets,
-                    &total_liabilities,
-                    &total_equity,
-                    &cash,
-                    &long_term_debt,
-                    &(shares_outstanding.map(|v| v as i64)),
-                ],
-            )
-            .await
-        {
-            tracing::error!(
-                "Failed to insert balance sheet for {} ({}): {}",
-                row.symbol,
-                period_date,
-                e
-            );
-        }
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO balance_sheets
+                        (symbol, frequency, period_date, total_assets, total_liabilities, total_equity, cash, long_term_debt, shares_outstanding)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    ON CONFLICT (symbol, frequency, period_date) DO UPDATE SET
+                        total_assets = EXCLUDED.total_assets,
+                        total_liabilities = EXCLUDED.total_liabilities,
+                        total_equity = EXCLUDED.total_equity,
+                        cash = EXCLUDED.cash,
+                        long_term_debt = EXCLUDED.long_term_debt,
+                        shares_outstanding = EXCLUDED.shares_outstanding,
+                        received_at = CURRENT_TIMESTAMP",
+                    &[
+                        &row.symbol,
+                        &frequency,
+                        &period_date,
+                        &total_assets,
+                        &total_liabilities,
+                        &total_equity,
+                        &cash,
+                        &long_term_debt,
+                        &(shares_outstanding.map(|v| v as i64)),
+                    ],
+                )
+                .await,
+            &format!("balance sheet for {} ({})", row.symbol, period_date),
+        )
     }
 
-    async fn insert_cashflow(client: &Client, row: crate::connectors::yahoo::types::CashflowRow) {
+    async fn insert_cashflow(client: &Client, row: &CashflowRow) -> InsertOutcome {
+        let frequency = Self::stored_frequency(row.frequency);
         let period_date = match Self::extract_period_date(&row.inner.period) {
             Some(date) => date,
             None => {
-                tracing::warn!("Skipping cashflow for {}: invalid period", row.symbol);
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "cashflow for {}: period {:?} is not a date",
+                    row.symbol, row.inner.period
+                ));
             }
         };
+        let line_items = Self::insert_line_items(
+            client,
+            TtmTable::Cashflow,
+            &row.symbol,
+            frequency,
+            period_date,
+            &row.inner,
+        )
+        .await;
+        if line_items.is_failed() {
+            return line_items;
+        }
 
         let operating_cashflow = match Self::extract_amount(row.inner.operating_cashflow.as_ref()) {
             Some(val) => val,
             None => {
-                tracing::warn!(
-                    "Skipping cashflow for {}: missing operating_cashflow",
-                    row.symbol
-                );
-                return;
+                return InsertOutcome::Skipped(format!(
+                    "cashflow for {} ({}): missing operating_cashflow",
+                    row.symbol, period_date
+                ));
             }
         };
 
@@ -290,36 +808,101 @@ impl YahooMessageHandler {
         let free_cash_flow = Self::extract_amount(row.inner.free_cash_flow.as_ref());
         let net_income = Self::extract_amount(row.inner.net_income.as_ref());
 
-        if let Err(e) = client
-            .execute(
-                "INSERT INTO quarterly_cashflow_statements 
-                    (symbol, period_date, operating_cashflow, capital_expenditures, free_cash_flow, net_income)
-                VALUES ($1, $2, $3, $4, $5, $6)
-                ON CONFLICT (symbol, period_date) DO NOTHING",
-                &[
-                    &row.symbol,
-                    &period_date,
-                    &operating_cashflow,
-                    &capital_expenditures,
-                    &free_cash_flow,
-                    &net_income,
-                ],
-            )
-            .await
-        {
-            tracing::error!(
-                "Failed to insert cashflow for {} ({}): {}",
-                row.symbol,
-                period_date,
-                e
-            );
-        }
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO cashflow_statements
+                        (symbol, frequency, period_date, operating_cashflow, capital_expenditures, free_cash_flow, net_income)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    ON CONFLICT (symbol, frequency, period_date) DO UPDATE SET
+                        operating_cashflow = EXCLUDED.operating_cashflow,
+                        capital_expenditures = EXCLUDED.capital_expenditures,
+                        free_cash_flow = EXCLUDED.free_cash_flow,
+                        net_income = EXCLUDED.net_income,
+                        received_at = CURRENT_TIMESTAMP",
+                    &[
+                        &row.symbol,
+                        &frequency,
+                        &period_date,
+                        &operating_cashflow,
+                        &capital_expenditures,
+                        &free_cash_flow,
+                        &net_income,
+                    ],
+                )
+                .await,
+            &format!("cashflow for {} ({})", row.symbol, period_date),
+        )
+    }
+
+    async fn insert_price_bar(client: &Client, bar: PriceBar) -> InsertOutcome {
+        // The latest bar is still forming, so later fetches overwrite it
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO price_bars
+                        (symbol, bar_interval, ts, open, high, low, close, volume)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    ON CONFLICT (symbol, bar_interval, ts) DO UPDATE SET
+                        open = EXCLUDED.open,
+                        high = EXCLUDED.high,
+                        low = EXCLUDED.low,
+                        close = EXCLUDED.close,
+                        volume = EXCLUDED.volume,
+                        received_at = CURRENT_TIMESTAMP",
+                    &[
+                        &bar.symbol,
+                        &bar.interval.as_str(),
+                        &bar.timestamp,
+                        &bar.open,
+                        &bar.high,
+                        &bar.low,
+                        &bar.close,
+                        &bar.volume,
+                    ],
+                )
+                .await,
+            &format!("price bar for {} ({})", bar.symbol, bar.timestamp),
+        )
+    }
+
+    async fn insert_dividend(client: &Client, dividend: Dividend) -> InsertOutcome {
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO dividends (symbol, ex_date, amount)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (symbol, ex_date) DO NOTHING",
+                    &[&dividend.symbol, &dividend.ex_date, &dividend.amount],
+                )
+                .await,
+            &format!("dividend for {} ({})", dividend.symbol, dividend.ex_date),
+        )
+    }
+
+    async fn insert_split(client: &Client, split: Split) -> InsertOutcome {
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO splits (symbol, split_date, numerator, denominator)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (symbol, split_date) DO NOTHING",
+                    &[
+                        &split.symbol,
+                        &split.date,
+                        &(split.numerator as i32),
+                        &(split.denominator as i32),
+                    ],
+                )
+                .await,
+            &format!("split for {} ({})", split.symbol, split.date),
+        )
     }
 
     async fn insert_calendar(
         client: &Client,
         calendar_entry: crate::connectors::yahoo::types::CalendarEntry,
-    ) {
+    ) -> InsertOutcome {
         // (date_type IN ('earnings', 'ex_dividend', 'dividend_payment')),
         // Convert enum to string for PostgreSQL
         let date_type_str = match calendar_entry.date_type {
@@ -328,20 +911,76 @@ impl YahooMessageHandler {
             CalendarDateType::DividendPayment => "dividend_payment",
         };
 
-        if let Err(e) = client
-            .execute(
-                "INSERT INTO calendar_dates (symbol, date_type, date_utc)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (symbol, date_type, date_utc) DO NOTHING",
-                &[&calendar_entry.symbol, &date_type_str, &calendar_entry.date],
-            )
-            .await
-        {
-            tracing::error!(
-                "Failed to insert earnings date for {}: {}",
-                &calendar_entry.symbol,
-                e
-            );
-        }
+        InsertOutcome::from_execute(
+            client
+                .execute(
+                    "INSERT INTO calendar_dates (symbol, date_type, date_utc)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (symbol, date_type, date_utc) DO NOTHING",
+                    &[&calendar_entry.symbol, &date_type_str, &calendar_entry.date],
+                )
+                .await,
+            &format!(
+                "{} date for {} ({})",
+                date_type_str, calendar_entry.symbol, calendar_entry.date
+            ),
+        )
+    }
+}
+
+/// A numeric field of a statement row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineItem {
+    pub item: String,
+    pub value: Decimal,
+    pub currency: Option<String>,
+}
+
+/// Numeric fields of a serialized statement row, skipping its period and empty fields.
+///
+/// `Money` fields serialize as an object with an `amount` and a `currency` (a code or an
+/// object holding one); plain numbers such as share counts carry no currency.
+pub fn line_items(row: &Value) -> Vec<LineItem> {
+    let Some(fields) = row.as_object() else {
+        return Vec::new();
+    };
+
+    fields
+        .iter()
+        .filter(|(item, _)| item.as_str() != "period")
+        .filter_map(|(item, value)| {
+            let (value, currency) = match value {
+                Value::Object(money) => (
+                    decimal(money.get("amount")?)?,
+                    money.get("currency").and_then(currency_code),
+                ),
+                value => (decimal(value)?, None),
+            };
+            Some(LineItem {
+                item: item.clone(),
+                value,
+                currency,
+            })
+        })
+        .collect()
+}
+
+/// Amount of a number or numeric string, which may be in scientific notation.
+fn decimal(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.trim().to_string(),
+        _ => return None,
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+fn currency_code(value: &Value) -> Option<String> {
+    match value {
+        Value::String(code) => Some(code.clone()),
+        Value::Object(currency) => currency.get("code")?.as_str().map(str::to_string),
+        _ => None,
     }
 }
@@ -0,0 +1,72 @@
+use tickflow::core::{MessageOutcome, PartialWrite};
+use tickflow::storage::{BatchOutcome, InsertOutcome};
+
+#[test]
+fn batch_outcome_counts_rows_and_fails_on_failed_rows() {
+    let mut outcome = BatchOutcome::default();
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record(InsertOutcome::Duplicate);
+    outcome.record(InsertOutcome::Skipped(
+        "income statement for AAPL: no period".into(),
+    ));
+    let outcome = outcome.into_result().unwrap();
+    assert_eq!(
+        (
+            outcome.inserted,
+            outcome.duplicates,
+            outcome.skipped,
+            outcome.failed
+        ),
+        (2, 1, 1, 0)
+    );
+
+    let mut outcome = BatchOutcome::default();
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record(InsertOutcome::Failed(
+        "split for AAPL: connection closed".into(),
+    ));
+    let err = outcome.into_result().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<PartialWrite>(),
+        Some(&PartialWrite {
+            failed: 1,
+            total: 2,
+            first: "split for AAPL: connection closed".into(),
+        })
+    );
+    let err = err.to_string();
+    assert!(err.contains("1 of 2 rows failed"), "{err}");
+    assert!(err.contains("split for AAPL"), "{err}");
+}
+
+#[test]
+fn skipped_rows_are_not_rejected() {
+    let mut outcome = BatchOutcome::default();
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record(InsertOutcome::Skipped(
+        "income statement for AAPL: no period".into(),
+    ));
+    assert_eq!(
+        outcome.messages,
+        [
+            MessageOutcome::Ok,
+            MessageOutcome::Skipped("income statement for AAPL: no period".into())
+        ]
+    );
+}
+
+#[test]
+fn failed_derived_rows_reject_their_source_messages() {
+    let mut outcome = BatchOutcome::default();
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record(InsertOutcome::Duplicate);
+    outcome.record(InsertOutcome::Inserted);
+    outcome.record_derived_error("TTM income_statements for AAPL: deadlock".into(), &[1, 2]);
+
+    assert_eq!(outcome.messages[0], MessageOutcome::Ok);
+    assert!(outcome.messages[1].is_rejected());
+    assert!(outcome.messages[2].is_rejected());
+    let err = outcome.into_result().unwrap_err().to_string();
+    assert!(err.contains("TTM income_statements for AAPL"), "{err}");
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use tickflow::core::{Checkpoint, CheckpointStore, FileCheckpointStore, TrailingCheckpoint};
+
+#[tokio::test]
+async fn file_checkpoint_store_saves_loads_and_clears() {
+    let dir = std::env::temp_dir().join(format!("tickflow-checkpoint-{}", std::process::id()));
+    let store = FileCheckpointStore::new(&dir);
+
+    assert_eq!(store.load("markets").await.unwrap(), None);
+
+    store.save("markets", "MTAw").await.unwrap();
+    store.save("markets", "MjAw").await.unwrap();
+    store.save("symbols", "AAPL").await.unwrap();
+    assert_eq!(
+        store.load("markets").await.unwrap().as_deref(),
+        Some("MjAw")
+    );
+
+    let checkpoint = Checkpoint::new(Arc::new(store), "symbols");
+    assert_eq!(checkpoint.load().await.unwrap().as_deref(), Some("AAPL"));
+    checkpoint.clear().await.unwrap();
+    checkpoint.clear().await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap(), None);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn trailing_checkpoint_saves_positions_once_out_of_flight() {
+    let dir = std::env::temp_dir().join(format!("tickflow-trailing-{}", std::process::id()));
+    let checkpoint = Checkpoint::new(Arc::new(FileCheckpointStore::new(&dir)), "symbols");
+    let mut trailing = TrailingCheckpoint::new(checkpoint.clone(), 2);
+
+    // One batch per symbol: a symbol is safe once two more batches were sent after it
+    trailing.advance("AAPL", 1).await.unwrap();
+    trailing.advance("MSFT", 2).await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap(), None);
+
+    trailing.advance("NVDA", 3).await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap().as_deref(), Some("AAPL"));
+
+    // Symbols without batches move nothing out of flight
+    trailing.advance("TSLA", 3).await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap().as_deref(), Some("AAPL"));
+
+    trailing.advance("AMZN", 5).await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap().as_deref(), Some("TSLA"));
+
+    trailing.clear().await.unwrap();
+    assert_eq!(checkpoint.load().await.unwrap(), None);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use tickflow::config::AppConfig;
+
+const CONFIG: &str = r#"
+database_url = "postgres://localhost/tickflow"
+channel_capacity = 500
+
+[yahoo]
+proxies_path = "proxies.txt"
+
+[pipelines.statements]
+source = "yahoo"
+sink = "postgres"
+symbols = ["AAPL", "PLTR"]
+
+[pipelines.crypto]
+source = "alpaca"
+sink = "file"
+channel_capacity = 2000
+"#;
+
+#[test]
+fn pipelines_only_validate_the_connectors_they_use() {
+    let config = AppConfig::from_toml(CONFIG).expect("valid config");
+
+    let statements = config
+        .pipeline("statements")
+        .expect("yahoo needs no credentials");
+    assert_eq!(statements.symbols, vec!["AAPL", "PLTR"]);
+    assert_eq!(config.channel_capacity_for(statements), 500);
+
+    let error = config.pipeline("crypto").unwrap_err().to_string();
+    for problem in [
+        "APCA_API_KEY_ID must be set",
+        "APCA_API_SECRET_KEY must be set",
+        "APCA_WS_URL must be set",
+        "file sink needs options.path",
+    ] {
+        assert!(error.contains(problem), "{problem:?} missing from {error}");
+    }
+}
+
+#[test]
+fn environment_overrides_the_file() {
+    let env: HashMap<&str, &str> = HashMap::from([
+        ("DATABASE_URL", "postgres://db/override"),
+        ("APCA_API_KEY_ID", "key"),
+        ("APCA_API_SECRET_KEY", "secret"),
+        ("APCA_WS_URL", "wss://stream"),
+        ("PROXIES_PATH", ""),
+    ]);
+    let config = AppConfig::from_toml(CONFIG)
+        .and_then(|config| config.with_overrides(|key| env.get(key).map(|v| v.to_string())))
+        .expect("valid config");
+
+    assert_eq!(config.database_url().unwrap(), "postgres://db/override");
+    assert_eq!(config.yahoo.proxies_path.as_deref(), Some("proxies.txt"));
+    assert_eq!(config.alpaca().unwrap().ws_url, "wss://stream");
+}
+
+#[test]
+fn unknown_connectors_are_reported_together() {
+    let config = AppConfig::from_toml(
+        r#"
+        [pipelines.broken]
+        source = "kraken"
+        sink = "s3"
+        "#,
+    )
+    .expect("valid toml");
+
+    let error = config.pipeline("broken").unwrap_err().to_string();
+    assert!(error.contains("unknown source \"kraken\""));
+    assert!(error.contains("unknown sink \"s3\""));
+    assert!(config.pipeline("missing").is_err());
+}
+
+#[test]
+fn overflow_policies_are_validated() {
+    let config = AppConfig::from_toml(
+        r#"
+        [pipelines.statements]
+        source = "yahoo"
+        sink = "file"
+        options = { path = "captures" }
+        overflow = { policy = "conflate_latest" }
+
+        [pipelines.spill]
+        source = "yahoo"
+        sink = "file"
+        options = { path = "captures" }
+        overflow = { policy = "spill_to_disk" }
+        "#,
+    )
+    .expect("valid toml");
+
+    let error = config.pipeline("statements").unwrap_err().to_string();
+    assert!(error.contains("conflate_latest is only supported for alpaca sources"));
+    let error = config.pipeline("spill").unwrap_err().to_string();
+    assert!(error.contains("spill_to_disk needs overflow.spill_path"));
+    assert!(AppConfig::from_toml("[pipelines.x]\nsource = \"yahoo\"\nsink = \"file\"\noverflow = { policy = \"drop_everything\" }").is_err());
+}
+
+#[test]
+fn polymarket_sync_cannot_be_scheduled() {
+    let config = AppConfig::from_toml(
+        r#"
+        [pipelines.markets]
+        source = "polymarket"
+        sink = "file"
+        options = { path = "captures", mode = "sync" }
+        schedule = { interval_secs = 60 }
+        "#,
+    )
+    .expect("valid toml");
+
+    let error = config.pipeline("markets").unwrap_err().to_string();
+    assert!(
+        error.contains("polymarket sync already repeats every interval_secs"),
+        "{error}"
+    );
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+use flate2::read::MultiGzDecoder;
+use tickflow::connectors::alpaca::types::AlpacaMessage;
+use tickflow::core::MessageSink;
+use tickflow::storage::{FileFormat, FileSink};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tickflow-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn sample_trade(id: u64) -> AlpacaMessage {
+    let json = format!(
+        r#"{{"T":"t","S":"MSFT","i":{id},"x":"V","p":380.5,"s":50,"t":"2024-01-01T10:00:02Z"}}"#
+    );
+    serde_json::from_str(&json).unwrap()
+}
+
+fn sorted_files(dir: &PathBuf) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn file_sink_writes_json_lines_and_rotates_by_size() {
+    let dir = temp_dir("jsonl-rotation");
+    let sink = FileSink::new(&dir, FileFormat::JsonLines)
+        .prefix("alpaca")
+        .max_file_size(1);
+
+    sink.handle_batch(vec![sample_trade(1), sample_trade(2)])
+        .await
+        .expect("first batch");
+    sink.handle_batch(vec![sample_trade(3)])
+        .await
+        .expect("second batch");
+
+    let files = sorted_files(&dir);
+    assert_eq!(files.len(), 2);
+    assert!(
+        files[0]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .ends_with("-0000.jsonl")
+    );
+
+    let first = fs::read_to_string(&files[0]).unwrap();
+    let ids: Vec<u64> = first
+        .lines()
+        .map(
+            |line| match serde_json::from_str::<AlpacaMessage>(line).unwrap() {
+                AlpacaMessage::Trade(trade) => trade.id,
+                _ => panic!("Expected Trade message"),
+            },
+        )
+        .collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    let second = fs::read_to_string(&files[1]).unwrap();
+    assert_eq!(second.lines().count(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn file_sink_writes_gzip_csv_with_header() {
+    let dir = temp_dir("csv-gzip");
+    let sink = FileSink::new(&dir, FileFormat::Csv).gzip(true);
+
+    sink.handle_batch(vec![sample_trade(7)])
+        .await
+        .expect("batch written");
+    drop(sink);
+
+    let files = sorted_files(&dir);
+    assert_eq!(files.len(), 1);
+    assert!(files[0].to_string_lossy().ends_with(".csv.gz"));
+
+    let mut content = String::new();
+    MultiGzDecoder::new(fs::File::open(&files[0]).unwrap())
+        .read_to_string(&mut content)
+        .unwrap();
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("received_at,message"));
+    let row = lines.next().expect("data row");
+    assert!(row.contains(r#"""T"":""t"""#));
+    assert!(lines.next().is_none());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn file_sink_close_finishes_the_gzip_stream() {
+    use flate2::read::GzDecoder;
+
+    let dir = temp_dir("gzip-close");
+    let sink = FileSink::new(&dir, FileFormat::JsonLines).gzip(true);
+
+    sink.handle_batch(vec![sample_trade(1), sample_trade(2)])
+        .await
+        .expect("batch written");
+    sink.close().await.expect("file finished");
+
+    // Read back while the sink is still alive: the trailer must already be on disk
+    let files = sorted_files(&dir);
+    assert_eq!(files.len(), 1);
+    let mut content = String::new();
+    GzDecoder::new(fs::File::open(&files[0]).unwrap())
+        .read_to_string(&mut content)
+        .expect("complete gzip stream");
+    assert_eq!(content.lines().count(), 2);
+
+    drop(sink);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,211 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Result, anyhow};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use tickflow::core::{Message, MessageBatch, MessageSink, MessageSource, PartialWrite};
+use tickflow::pipeline::{
+    HealthServer, PipelineStatus, RestartPolicy, StatusRegistry, Supervisor, TickflowBuilder,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tick(u32);
+
+impl Message for Tick {}
+
+struct VecSource(Vec<MessageBatch<Tick>>);
+
+impl MessageSource<Tick> for VecSource {
+    fn run<'a>(
+        &'a mut self,
+        tx: mpsc::Sender<MessageBatch<Tick>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            for batch in std::mem::take(&mut self.0) {
+                tx.send(batch).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Fails every batch containing tick 0.
+struct PickySink;
+
+impl MessageSink<Tick> for PickySink {
+    fn name(&self) -> &'static str {
+        "picky"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        batch: MessageBatch<Tick>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            match batch.contains(&Tick(0)) {
+                true => Err(anyhow!("connection refused")),
+                false => Ok(()),
+            }
+        })
+    }
+}
+
+/// Fails to write one row of every batch, like a database rejecting a constraint.
+struct PartialSink;
+
+impl MessageSink<Tick> for PartialSink {
+    fn name(&self) -> &'static str {
+        "partial"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        batch: MessageBatch<Tick>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            Err(PartialWrite {
+                failed: 1,
+                total: batch.len(),
+                first: "tick: value out of range".to_string(),
+            }
+            .into())
+        })
+    }
+}
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn pipeline_reports_counts_and_sink_errors() {
+    let registry = StatusRegistry::new();
+    let status = registry.pipeline("ticks");
+    let source = VecSource(vec![vec![Tick(0)], vec![Tick(1), Tick(2)]]);
+
+    TickflowBuilder::new(source, PickySink)
+        .status(status.clone())
+        .run()
+        .await
+        .unwrap();
+
+    let snapshot = registry.pipeline("ticks").snapshot();
+    assert_eq!(snapshot.messages, 3);
+    assert_eq!(snapshot.batches, 2);
+    assert_eq!(snapshot.errors, 1);
+    assert_eq!(snapshot.last_error.as_deref(), Some("connection refused"));
+    assert_eq!(snapshot.sink_reachable, Some(true));
+    assert!(snapshot.last_message_at.is_some());
+    assert!(!snapshot.running);
+    assert_eq!(snapshot.problems(), ["ticks: source is not running"]);
+}
+
+#[tokio::test]
+async fn full_channel_is_reported_as_saturated() {
+    let status = PipelineStatus::new("ticks");
+    status.set_running(true);
+    let (tx, _rx) = mpsc::channel(2);
+    status.watch_channel(&tx);
+    tx.send(vec![Tick(1)]).await.unwrap();
+    assert!(status.snapshot().problems().is_empty());
+
+    tx.send(vec![Tick(2)]).await.unwrap();
+    let snapshot = status.snapshot();
+    assert_eq!((snapshot.queued, snapshot.capacity), (2, 2));
+    assert_eq!(snapshot.problems(), ["ticks: channel is saturated (2/2)"]);
+}
+
+#[tokio::test]
+async fn server_exposes_liveness_readiness_and_status() {
+    let registry = StatusRegistry::new();
+    let server = HealthServer::bind("127.0.0.1:0", registry.clone())
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.serve());
+
+    let status = registry.pipeline("quotes");
+    status.set_running(true);
+    status.set_connected(true);
+    status.record_batch(5);
+    let ready = get(addr, "/readyz").await;
+    assert!(ready.starts_with("HTTP/1.1 200"), "{ready}");
+
+    status.record_sink_error("database is down");
+    let live = get(addr, "/healthz").await;
+    assert!(live.starts_with("HTTP/1.1 200"), "{live}");
+    let ready = get(addr, "/readyz").await;
+    assert!(ready.starts_with("HTTP/1.1 503"), "{ready}");
+    assert!(ready.contains("quotes: sink is unreachable: database is down"));
+
+    let report = get(addr, "/status").await;
+    let body = report.split("\r\n\r\n").nth(1).unwrap();
+    let json: serde_json::Value = serde_json::from_str(body).unwrap();
+    let pipeline = &json["pipelines"][0];
+    assert_eq!(pipeline["name"], "quotes");
+    assert_eq!(pipeline["ready"], false);
+    assert_eq!(pipeline["connected"], true);
+    assert_eq!(pipeline["messages"], 5);
+    assert_eq!(pipeline["errors"], 1);
+    assert!(pipeline["last_message_at"].is_string());
+}
+
+#[tokio::test]
+async fn failed_rows_leave_the_sink_reachable() {
+    let status = PipelineStatus::new("ticks");
+    let source = VecSource(vec![vec![Tick(1)], vec![Tick(2), Tick(3)]]);
+
+    // Failed rows are not a sink outage, so they do not stop the pipeline either
+    TickflowBuilder::new(source, PartialSink)
+        .status(status.clone())
+        .max_sink_errors(1)
+        .run()
+        .await
+        .unwrap();
+
+    let snapshot = status.snapshot();
+    assert_eq!(snapshot.errors, 2);
+    assert_eq!(snapshot.sink_reachable, Some(true));
+    assert_eq!(
+        snapshot.last_error.as_deref(),
+        Some("1 of 2 rows failed to insert, first: tick: value out of range")
+    );
+}
+
+#[tokio::test]
+async fn finished_one_shot_pipelines_stay_ready() {
+    let registry = StatusRegistry::new();
+    let status = registry.pipeline("backfill");
+
+    Supervisor::new()
+        .status(registry.clone())
+        .pipeline("backfill", RestartPolicy::default(), move || {
+            let source = VecSource(vec![vec![Tick(1)]]);
+            TickflowBuilder::new(source, PickySink)
+                .status(status.clone())
+                .run()
+        })
+        .pipeline("broken", RestartPolicy::default(), || async {
+            Err(anyhow!("source crashed"))
+        })
+        .run()
+        .await
+        .unwrap_err();
+
+    let snapshots = registry.snapshots();
+    assert_eq!(snapshots[0].name, "backfill");
+    assert!(snapshots[0].finished);
+    assert!(snapshots[0].problems().is_empty());
+    // A pipeline that gave up on an error is not finished
+    assert_eq!(snapshots[1].name, "broken");
+    assert!(!snapshots[1].finished);
+    assert_eq!(snapshots[1].problems(), ["broken: source is not running"]);
+}
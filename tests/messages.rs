@@ -195,3 +195,25 @@ fn test_parse_malformed_json() {
     let result = serde_json::from_str::<AlpacaMessage>(malformed_json);
     assert!(result.is_err());
 }
+
+// Part 4: Websocket protocol classification
+
+#[test]
+fn test_alpaca_protocol_classifies_control_messages() {
+    use tickflow::connectors::alpaca::AlpacaProtocol;
+    use tickflow::connectors::websocket::{Control, Protocol};
+
+    let protocol = AlpacaProtocol::new("wss://example", "key", "secret", &[], &["AAPL"], &[]);
+    let frame = r#"[{"T":"success","msg":"authenticated"},{"T":"error","code":406,"msg":"connection limit exceeded"},{"T":"error","code":405,"msg":"symbol limit exceeded"},{"T":"b","S":"AAPL","o":1.0,"h":1.0,"l":1.0,"c":1.0,"v":1,"t":"2024-01-01T10:00:00Z"}]"#;
+    let messages = protocol.parse(frame).unwrap();
+
+    let controls: Vec<Control> = messages.iter().map(|m| protocol.classify(m)).collect();
+    assert_eq!(controls[0], Control::Status("authenticated".to_string()));
+    assert!(matches!(controls[1], Control::Fatal(_)));
+    assert!(matches!(controls[2], Control::Error(_)));
+    assert_eq!(controls[3], Control::Data);
+
+    let subscribe: serde_json::Value =
+        serde_json::from_str(&protocol.subscribe_payload().unwrap()).unwrap();
+    assert_eq!(subscribe["quotes"], serde_json::json!(["AAPL"]));
+}
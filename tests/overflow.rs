@@ -0,0 +1,227 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, mpsc};
+
+use tickflow::core::{Message, MessageBatch, MessageSink, MessageSource};
+use tickflow::pipeline::{OverflowPolicy, PipelineStatus, TickflowBuilder};
+
+const SENT: u32 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Quote {
+    symbol: String,
+    seq: u32,
+}
+
+impl Message for Quote {}
+
+/// Sends `SENT` single-quote batches, alternating between two symbols.
+struct BurstSource {
+    done: Arc<Notify>,
+}
+
+impl MessageSource<Quote> for BurstSource {
+    fn run<'a>(
+        &'a mut self,
+        tx: mpsc::Sender<MessageBatch<Quote>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            for seq in 1..=SENT {
+                let symbol = if seq % 2 == 0 { "AAPL" } else { "MSFT" };
+                tx.send(vec![Quote {
+                    symbol: symbol.to_string(),
+                    seq,
+                }])
+                .await?;
+            }
+            self.done.notify_one();
+            Ok(())
+        })
+    }
+}
+
+/// Holds the first batch until the source has sent everything, so the channel overflows.
+#[derive(Clone)]
+struct StalledSink {
+    source_done: Arc<Notify>,
+    received: Arc<Mutex<Vec<Quote>>>,
+}
+
+impl MessageSink<Quote> for StalledSink {
+    fn name(&self) -> &'static str {
+        "stalled"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        batch: MessageBatch<Quote>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.received.lock().unwrap().is_empty() {
+                self.source_done.notified().await;
+            }
+            self.received.lock().unwrap().extend(batch);
+            Ok(())
+        })
+    }
+}
+
+async fn run(policy: OverflowPolicy<Quote>) -> (Vec<u32>, PipelineStatus) {
+    let done = Arc::new(Notify::new());
+    let sink = StalledSink {
+        source_done: done.clone(),
+        received: Arc::default(),
+    };
+    let status = PipelineStatus::new("quotes");
+
+    TickflowBuilder::new(BurstSource { done }, sink.clone())
+        .channel_capacity(2)
+        .overflow(policy)
+        .status(status.clone())
+        .run()
+        .await
+        .unwrap();
+
+    let received = sink
+        .received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|q| q.seq)
+        .collect();
+    (received, status)
+}
+
+#[tokio::test]
+async fn drop_newest_keeps_the_head_and_counts_drops() {
+    let (received, status) = run(OverflowPolicy::DropNewest).await;
+
+    assert!(received.len() < SENT as usize);
+    assert_eq!(received, (1..=received.len() as u32).collect::<Vec<_>>());
+    assert_eq!(
+        status.snapshot().dropped,
+        (SENT as usize - received.len()) as u64
+    );
+}
+
+#[tokio::test]
+async fn drop_oldest_keeps_the_latest_batches() {
+    let (received, status) = run(OverflowPolicy::DropOldest).await;
+
+    assert!(received.len() < SENT as usize);
+    assert_eq!(received.last(), Some(&SENT));
+    assert!(received.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(
+        status.snapshot().dropped,
+        (SENT as usize - received.len()) as u64
+    );
+}
+
+#[tokio::test]
+async fn conflation_keeps_the_latest_quote_per_symbol() {
+    let policy = OverflowPolicy::conflate_latest(|quote: &Quote| Some(quote.symbol.clone()));
+    let (received, status) = run(policy).await;
+
+    assert!(received.contains(&SENT) && received.contains(&(SENT - 1)));
+    assert!(received.len() < SENT as usize);
+    assert_eq!(
+        status.snapshot().dropped,
+        (SENT as usize - received.len()) as u64
+    );
+}
+
+#[tokio::test]
+async fn spilling_delivers_everything_in_order() {
+    let dir = std::env::temp_dir().join(format!("tickflow-spill-{}", std::process::id()));
+    let path = dir.join("quotes.jsonl");
+    let (received, status) = run(OverflowPolicy::spill_to_disk(&path)).await;
+
+    assert_eq!(received, (1..=SENT).collect::<Vec<_>>());
+    let snapshot = status.snapshot();
+    assert_eq!(snapshot.dropped, 0);
+    assert!(snapshot.spilled > 0);
+    assert!(!path.exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn spilled_batches_left_by_a_crash_are_replayed_first() {
+    let dir = std::env::temp_dir().join(format!("tickflow-respill-{}", std::process::id()));
+    let path = dir.join("quotes.jsonl");
+    std::fs::create_dir_all(&dir).unwrap();
+    // Two complete batches and one torn by the crash
+    std::fs::write(
+        &path,
+        concat!(
+            r#"[{"symbol":"AAPL","seq":101}]"#,
+            "\n",
+            r#"[{"symbol":"MSFT","seq":102}]"#,
+            "\n",
+            r#"[{"symbol":"AA"#,
+        ),
+    )
+    .unwrap();
+
+    let (received, _) = run(OverflowPolicy::spill_to_disk(&path)).await;
+
+    let mut expected = vec![101, 102];
+    expected.extend(1..=SENT);
+    assert_eq!(received, expected);
+    assert!(!path.exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Records the pipeline's queue level once the source has sent everything.
+struct ProbingSink {
+    source_done: Arc<Notify>,
+    status: PipelineStatus,
+    level: Arc<Mutex<Option<(usize, usize)>>>,
+}
+
+impl MessageSink<Quote> for ProbingSink {
+    fn name(&self) -> &'static str {
+        "probing"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        _batch: MessageBatch<Quote>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.level.lock().unwrap().is_none() {
+                self.source_done.notified().await;
+                let snapshot = self.status.snapshot();
+                *self.level.lock().unwrap() = Some((snapshot.queued, snapshot.capacity));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn relay_buffer_is_reported_as_the_queue() {
+    let done = Arc::new(Notify::new());
+    let status = PipelineStatus::new("quotes");
+    let level = Arc::new(Mutex::new(None));
+    let sink = ProbingSink {
+        source_done: done.clone(),
+        status: status.clone(),
+        level: level.clone(),
+    };
+
+    TickflowBuilder::new(BurstSource { done }, sink)
+        .channel_capacity(2)
+        .overflow(OverflowPolicy::DropNewest)
+        .status(status.clone())
+        .run()
+        .await
+        .unwrap();
+
+    // The source channel is drained at once; the relay buffer is what fills up
+    assert_eq!(*level.lock().unwrap(), Some((2, 2)));
+    assert_eq!(status.snapshot().queued, 0);
+}
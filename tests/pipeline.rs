@@ -3,8 +3,8 @@ use std::sync::Arc;
 use anyhow::{Result, anyhow};
 use tokio::sync::{Mutex, mpsc};
 
-use tickflow::core::{Message, MessageBatch, MessageSink, MessageSource};
-use tickflow::pipeline::{MessageProcessor, SPSCDataFeed};
+use tickflow::core::{Message, MessageBatch, MessageOutcome, MessageSink, MessageSource};
+use tickflow::pipeline::{MessageProcessor, OverflowPolicy, SPSCDataFeed};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct TestMessage(&'static str);
@@ -51,6 +51,24 @@ impl MessageSource<TestMessage> for MockSource {
     }
 }
 
+/// Sends the same batch until the receiver goes away.
+struct EndlessSource;
+
+impl MessageSource<TestMessage> for EndlessSource {
+    fn run<'a>(
+        &'a mut self,
+        tx: mpsc::Sender<MessageBatch<TestMessage>>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            loop {
+                tx.send(vec![TestMessage("tick")])
+                    .await
+                    .map_err(|err| anyhow!("send failed: {err}"))?;
+            }
+        })
+    }
+}
+
 #[derive(Default)]
 struct MockSinkState {
     batches: Vec<MessageBatch<TestMessage>>,
@@ -109,6 +127,76 @@ impl MessageSink<TestMessage> for MockSink {
     }
 }
 
+/// Rejects the named messages on their first `rejections` deliveries and skips the
+/// `skip` ones on every delivery.
+#[derive(Clone)]
+struct RejectingSink {
+    reject: Vec<&'static str>,
+    skip: Vec<&'static str>,
+    rejections: Arc<Mutex<usize>>,
+    accepted: Arc<Mutex<Vec<TestMessage>>>,
+    skipped: Arc<Mutex<Vec<TestMessage>>>,
+}
+
+impl RejectingSink {
+    fn new(reject: Vec<&'static str>, rejections: usize) -> Self {
+        Self {
+            reject,
+            skip: Vec::new(),
+            rejections: Arc::new(Mutex::new(rejections)),
+            accepted: Arc::new(Mutex::new(Vec::new())),
+            skipped: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn skipping(mut self, skip: Vec<&'static str>) -> Self {
+        self.skip = skip;
+        self
+    }
+}
+
+impl MessageSink<TestMessage> for RejectingSink {
+    fn name(&self) -> &'static str {
+        "rejecting"
+    }
+
+    fn handle_batch<'a>(
+        &'a self,
+        _batch: MessageBatch<TestMessage>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async { Err(anyhow!("only outcome-based delivery is supported")) })
+    }
+
+    fn handle_batch_outcomes<'a>(
+        &'a self,
+        batch: MessageBatch<TestMessage>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<MessageOutcome>>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            let mut rejections = self.rejections.lock().await;
+            let reject = *rejections > 0;
+            *rejections = rejections.saturating_sub(1);
+
+            let mut accepted = self.accepted.lock().await;
+            let mut skipped = self.skipped.lock().await;
+            Ok(batch
+                .into_iter()
+                .map(|message| {
+                    if self.skip.contains(&message.0) {
+                        skipped.push(message.clone());
+                        MessageOutcome::Skipped(format!("{} is incomplete", message.0))
+                    } else if reject && self.reject.contains(&message.0) {
+                        MessageOutcome::Rejected(format!("{} is invalid", message.0))
+                    } else {
+                        accepted.push(message);
+                        MessageOutcome::Ok
+                    }
+                })
+                .collect())
+        })
+    }
+}
+
 #[tokio::test]
 async fn tickflow_builder_start_processes_batches() {
     let source_batches = vec![
@@ -191,3 +279,128 @@ async fn datafeed_completes_when_source_fails_midstream() {
     assert_eq!(batches.len(), 1);
     assert_eq!(batches[0], vec![TestMessage("only")]);
 }
+
+#[tokio::test]
+async fn processor_dead_letters_only_rejected_messages() {
+    let sink = RejectingSink::new(vec!["bad"], usize::MAX);
+    let dead_letter = MockSink::new("dead-letter");
+    let source = MockSource::new(vec![vec![
+        TestMessage("good"),
+        TestMessage("bad"),
+        TestMessage("fine"),
+    ]]);
+
+    let handles = SPSCDataFeed::builder(source, sink.clone())
+        .dead_letter(dead_letter.clone())
+        .start()
+        .await
+        .expect("failed to start data feed");
+    handles.source.await.expect("source task panicked");
+    handles.processor.await.expect("processor task panicked");
+
+    assert_eq!(
+        *sink.accepted.lock().await,
+        vec![TestMessage("good"), TestMessage("fine")]
+    );
+    assert_eq!(
+        dead_letter.handled_batches().await,
+        vec![vec![TestMessage("bad")]]
+    );
+}
+
+#[tokio::test]
+async fn processor_retries_only_rejected_messages() {
+    let sink = RejectingSink::new(vec!["late"], 1);
+    let dead_letter = MockSink::new("dead-letter");
+    let processor = MessageProcessor::new(sink.clone())
+        .retries(2)
+        .dead_letter(dead_letter.clone());
+    let (tx, rx) = mpsc::channel(4);
+
+    tx.send(vec![TestMessage("early"), TestMessage("late")])
+        .await
+        .expect("send batch");
+    drop(tx);
+    processor
+        .process_messages(rx)
+        .await
+        .expect("processor returned error");
+
+    assert_eq!(
+        *sink.accepted.lock().await,
+        vec![TestMessage("early"), TestMessage("late")]
+    );
+    assert!(dead_letter.handled_batches().await.is_empty());
+}
+
+#[tokio::test]
+async fn processor_neither_retries_nor_dead_letters_skipped_messages() {
+    let sink = RejectingSink::new(Vec::new(), 0).skipping(vec!["partial"]);
+    let dead_letter = MockSink::new("dead-letter");
+    let processor = MessageProcessor::new(sink.clone())
+        .retries(2)
+        .dead_letter(dead_letter.clone());
+    let (tx, rx) = mpsc::channel(4);
+
+    tx.send(vec![TestMessage("complete"), TestMessage("partial")])
+        .await
+        .expect("send batch");
+    drop(tx);
+    processor
+        .process_messages(rx)
+        .await
+        .expect("processor returned error");
+
+    assert_eq!(*sink.accepted.lock().await, vec![TestMessage("complete")]);
+    assert_eq!(*sink.skipped.lock().await, vec![TestMessage("partial")]);
+    assert!(dead_letter.handled_batches().await.is_empty());
+}
+
+#[tokio::test]
+async fn processor_stops_after_consecutive_sink_errors() {
+    let send = |tx: mpsc::Sender<MessageBatch<TestMessage>>| async move {
+        for name in ["first", "second", "third"] {
+            tx.send(vec![TestMessage(name)]).await.expect("send batch");
+        }
+    };
+
+    // A delivered batch resets the count
+    let sink = MockSink::with_failures("flaky", 1);
+    let (tx, rx) = mpsc::channel(4);
+    send(tx).await;
+    MessageProcessor::new(sink.clone())
+        .max_sink_errors(2)
+        .process_messages(rx)
+        .await
+        .expect("processor returned error");
+    assert_eq!(sink.handled_batches().await.len(), 3);
+
+    let sink = MockSink::with_failures("down", usize::MAX);
+    let (tx, rx) = mpsc::channel(4);
+    send(tx).await;
+    let err = MessageProcessor::new(sink.clone())
+        .max_sink_errors(2)
+        .process_messages(rx)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("failed 2 batches in a row"),
+        "{err}"
+    );
+    assert_eq!(sink.handled_batches().await.len(), 2);
+}
+
+#[tokio::test]
+async fn datafeed_fails_with_the_sink_error_and_stops_the_source() {
+    for overflow in [OverflowPolicy::Block, OverflowPolicy::DropNewest] {
+        let sink = MockSink::with_failures("down", usize::MAX);
+        let result = SPSCDataFeed::builder(EndlessSource, sink)
+            .channel_capacity(2)
+            .overflow(overflow)
+            .max_sink_errors(3)
+            .run()
+            .await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("down sink failed 3 batches"), "{err}");
+    }
+}
@@ -0,0 +1,343 @@
+use tickflow::connectors::polymarket::PolymarketMarketProtocol;
+use tickflow::connectors::polymarket::PolymarketMessage;
+use tickflow::connectors::websocket::Protocol;
+
+fn protocol() -> PolymarketMarketProtocol {
+    PolymarketMarketProtocol::new("wss://example", vec!["123".to_string()])
+}
+
+#[test]
+fn test_parse_book_snapshot_array() {
+    let frame = r#"[{"event_type":"book","asset_id":"123","market":"0xabc","bids":[{"price":"0.48","size":"30"}],"asks":[{"price":"0.52","size":"25"}],"timestamp":"1700000000000","hash":"0xhash"}]"#;
+    let messages = protocol().parse(frame).unwrap();
+
+    match &messages[..] {
+        [PolymarketMessage::Book(book)] => {
+            assert_eq!(book.asset_id, "123");
+            assert_eq!(book.bids[0].price, 0.48);
+            assert_eq!(book.asks[0].size, 25.0);
+            assert_eq!(book.timestamp, 1_700_000_000_000);
+        }
+        _ => panic!("Expected a single Book message"),
+    }
+}
+
+#[test]
+fn test_parse_price_change_and_trade_events() {
+    let price_change = r#"{"event_type":"price_change","market":"0xabc","price_changes":[{"asset_id":"123","price":"0.5","size":"200","side":"BUY","hash":"0x1","best_bid":"0.5","best_ask":"0.51"}],"timestamp":"1700000000001"}"#;
+    match &protocol().parse(price_change).unwrap()[..] {
+        [PolymarketMessage::PriceChange(change)] => {
+            assert_eq!(change.price_changes[0].side, "BUY");
+            assert_eq!(change.price_changes[0].best_ask, Some(0.51));
+        }
+        _ => panic!("Expected a single PriceChange message"),
+    }
+
+    let trade = r#"{"event_type":"last_trade_price","asset_id":"123","market":"0xabc","price":"0.456","size":"219.21","side":"BUY","fee_rate_bps":"0","timestamp":"1700000000002"}"#;
+    match &protocol().parse(trade).unwrap()[..] {
+        [PolymarketMessage::LastTrade(trade)] => {
+            assert_eq!(trade.price, 0.456);
+            assert_eq!(trade.fee_rate_bps, Some(0.0));
+        }
+        _ => panic!("Expected a single LastTrade message"),
+    }
+}
+
+#[test]
+fn test_unknown_events_and_pong_are_dropped() {
+    assert!(protocol().parse("PONG").unwrap().is_empty());
+    let unknown = r#"[{"event_type":"new_feature","asset_id":"123"}]"#;
+    assert!(protocol().parse(unknown).unwrap().is_empty());
+}
+
+#[test]
+fn test_subscribe_payload_lists_token_ids() {
+    let payload: serde_json::Value =
+        serde_json::from_str(&protocol().subscribe_payload().unwrap()).unwrap();
+    assert_eq!(payload["type"], "market");
+    assert_eq!(payload["assets_ids"], serde_json::json!(["123"]));
+    assert!(protocol().auth_payload().is_none());
+}
+
+#[test]
+fn test_market_typed_tokens_rewards_and_tags() {
+    use tickflow::connectors::polymarket::Market;
+
+    let json = r#"{"condition_id":"0xabc","tokens":[{"token_id":"1","outcome":"Yes","price":0.62,"winner":false},{"token_id":"2","outcome":"No","price":"0.38","winner":true,"extra_field":7}],"rewards":{"rates":null,"min_size":100,"max_spread":3.5},"tags":["Politics","Elections"]}"#;
+    let market: Market = serde_json::from_str(json).unwrap();
+
+    assert_eq!(market.tokens.len(), 2);
+    assert_eq!(market.tokens[0].outcome, "Yes");
+    assert_eq!(market.tokens[1].price, Some(0.38));
+    assert!(market.tokens[1].winner);
+    assert_eq!(market.tokens[1].extra["extra_field"], 7);
+    assert!(market.rewards.rates.is_empty());
+    assert_eq!(market.rewards.max_spread, Some(3.5));
+    assert_eq!(market.tags[1].label, "Elections");
+
+    let nulls: Market = serde_json::from_str(
+        r#"{"condition_id":"0xdef","tokens":null,"rewards":null,"tags":null}"#,
+    )
+    .unwrap();
+    assert!(nulls.tokens.is_empty() && nulls.tags.is_empty());
+}
+
+#[test]
+fn test_tags_serialize_in_their_original_shape() {
+    use tickflow::connectors::polymarket::Market;
+
+    let tags = serde_json::json!(["Politics", {"label":"Sports","slug":"sports","id":"7"}]);
+    let market: Market =
+        serde_json::from_value(serde_json::json!({"condition_id":"0xabc","tags":tags})).unwrap();
+
+    assert_eq!(market.tags[1].slug.as_deref(), Some("sports"));
+    assert_eq!(serde_json::to_value(&market.tags).unwrap(), tags);
+}
+
+#[test]
+fn test_diff_market_detects_lifecycle_changes() {
+    use tickflow::connectors::polymarket::sync::diff_market;
+    use tickflow::connectors::polymarket::{Market, MarketEventKind};
+
+    let open: Market = serde_json::from_str(r#"{"condition_id":"0xabc","active":true,"closed":false,"taker_base_fee":0,"tokens":[{"token_id":"1","outcome":"Yes","price":0.6,"winner":false},{"token_id":"2","outcome":"No","price":0.4,"winner":false}]}"#).unwrap();
+
+    let opened = diff_market(None, &open, 1).unwrap();
+    assert_eq!(opened.kind, MarketEventKind::Opened);
+    assert!(opened.changes.iter().all(|c| c.old_value.is_none()));
+
+    // Token price moves alone are not tracked
+    let mut repriced = open.clone();
+    repriced.tokens[0].price = Some(0.7);
+    assert!(diff_market(Some(&open), &repriced, 2).is_none());
+
+    let mut fee_change = open.clone();
+    fee_change.taker_base_fee = 200.0;
+    let updated = diff_market(Some(&open), &fee_change, 3).unwrap();
+    assert_eq!(updated.kind, MarketEventKind::Updated);
+    assert_eq!(updated.changes.len(), 1);
+    assert_eq!(updated.changes[0].field, "taker_base_fee");
+
+    let mut closed = open.clone();
+    closed.closed = true;
+    assert_eq!(
+        diff_market(Some(&open), &closed, 4).unwrap().kind,
+        MarketEventKind::Closed
+    );
+
+    let mut resolved = closed.clone();
+    resolved.tokens[0].winner = true;
+    let event = diff_market(Some(&open), &resolved, 5).unwrap();
+    assert_eq!(event.kind, MarketEventKind::Resolved);
+    assert!(
+        event
+            .changes
+            .iter()
+            .any(|c| c.field == "winning_outcome" && c.new_value.as_deref() == Some("Yes"))
+    );
+}
+
+/// Serves `route(path_and_query)` as JSON over HTTP and records every request target.
+async fn mock_clob(
+    route: fn(&str) -> Option<String>,
+) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await.unwrap() {
+                        0 => return,
+                        n => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split(' ').nth(1).unwrap_or_default().to_string();
+                let response = match route(&target) {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    ),
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                recorded.lock().unwrap().push(target);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    (host, requests)
+}
+
+async fn poll(
+    poller: tickflow::connectors::polymarket::PolymarketPricePoller,
+) -> Vec<PolymarketMessage> {
+    use tickflow::core::MessageSource;
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let mut poller = poller;
+    poller.run(tx).await.unwrap();
+    let mut messages = Vec::new();
+    while let Some(batch) = rx.recv().await {
+        messages.extend(batch);
+    }
+    messages
+}
+
+#[test]
+fn test_watchlist_accepts_filters_by_tag_and_activity() {
+    use tickflow::connectors::polymarket::{Market, Watchlist};
+
+    let market = |json: &str| serde_json::from_str::<Market>(json).unwrap();
+    let open_politics = market(r#"{"condition_id":"1","active":true,"tags":["Politics"]}"#);
+    let closed_politics =
+        market(r#"{"condition_id":"2","active":true,"closed":true,"tags":["Politics"]}"#);
+    let open_sports = market(r#"{"condition_id":"3","active":true,"tags":[{"label":"Sports"}]}"#);
+
+    let everything = Watchlist::default();
+    assert!(everything.accepts(&closed_politics) && everything.accepts(&open_sports));
+
+    let politics = Watchlist {
+        tags: vec!["Politics".to_string()],
+        ..Watchlist::default()
+    };
+    assert!(politics.accepts(&open_politics) && politics.accepts(&closed_politics));
+    assert!(!politics.accepts(&open_sports));
+
+    let active = Watchlist {
+        active_only: true,
+        ..politics
+    };
+    assert!(active.accepts(&open_politics));
+    assert!(!active.accepts(&closed_politics));
+}
+
+#[tokio::test]
+async fn test_poller_filters_by_liquidity_and_parses_prices() {
+    use std::time::Duration;
+    use tickflow::connectors::polymarket::{PolymarketPricePoller, Watchlist};
+
+    let (host, _) = mock_clob(|target| {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let body = match (path, query) {
+            ("/markets", _) => r#"{"data":[
+                {"condition_id":"a","active":true,"tags":["Politics"],"tokens":[{"token_id":"1"},{"token_id":"2"}]},
+                {"condition_id":"b","active":true,"tags":["Sports"],"tokens":[{"token_id":"3"}]}
+            ],"next_cursor":"LTE="}"#,
+            ("/book", "token_id=1") => r#"{"bids":[{"price":"0.5","size":"100"}],"asks":[]}"#,
+            ("/book", _) => r#"{"bids":[],"asks":[{"price":"0.9","size":"1"}]}"#,
+            ("/midpoint", _) => r#"{"mid":" 0.52 "}"#,
+            ("/spread", _) => r#"{"spread":"n/a"}"#,
+            ("/last-trade-price", _) => r#"{"price":"0.5","side":"BUY"}"#,
+            _ => return None,
+        };
+        Some(body.to_string())
+    })
+    .await;
+    let watchlist = Watchlist {
+        tags: vec!["Politics".to_string()],
+        min_liquidity: Some(10.0),
+        ..Watchlist::default()
+    };
+    let poller = PolymarketPricePoller::new(watchlist, Duration::ZERO, 0)
+        .host(&host)
+        .max_polls(1);
+
+    match &poll(poller).await[..] {
+        [PolymarketMessage::PriceSnapshot(snapshot)] => {
+            assert_eq!(snapshot.token_id, "1");
+            assert_eq!(snapshot.midpoint, Some(0.52));
+            assert_eq!(snapshot.spread, None);
+            assert_eq!(snapshot.last_trade_price, Some(0.5));
+            assert_eq!(snapshot.last_trade_side.as_deref(), Some("BUY"));
+        }
+        other => panic!("Expected a single snapshot of token 1, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_poller_history_cursor_requests_only_new_samples() {
+    use std::time::Duration;
+    use tickflow::connectors::polymarket::{PolymarketPricePoller, Watchlist};
+
+    let (host, requests) = mock_clob(|target| {
+        let body = if target.starts_with("/prices-history") {
+            if target.contains("interval=1d") {
+                r#"{"history":[{"t":100,"p":0.4},{"t":200,"p":0.5}]}"#
+            } else {
+                // Overlaps the previous round; the poller drops samples it already has
+                r#"{"history":[{"t":200,"p":0.5},{"t":300,"p":0.6}]}"#
+            }
+        } else {
+            "{}"
+        };
+        Some(body.to_string())
+    })
+    .await;
+    let poller = PolymarketPricePoller::new(Watchlist::tokens(vec!["1".into()]), Duration::ZERO, 0)
+        .host(&host)
+        .with_history("1d", 60)
+        .max_polls(2);
+
+    let points: Vec<i64> = poll(poller)
+        .await
+        .iter()
+        .filter_map(|message| match message {
+            PolymarketMessage::PricePoint(point) => Some(point.timestamp),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(points, [100, 200, 300]);
+
+    let history: Vec<String> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|target| target.starts_with("/prices-history"))
+        .cloned()
+        .collect();
+    assert_eq!(history.len(), 2);
+    assert!(history[1].contains("startTs=201") && !history[1].contains("interval"));
+}
+
+#[tokio::test]
+async fn test_poller_waits_the_request_delay_after_failed_requests() {
+    use std::time::{Duration, Instant};
+    use tickflow::connectors::polymarket::{PolymarketPricePoller, Watchlist};
+
+    let (host, requests) = mock_clob(|_| None).await;
+    let poller =
+        PolymarketPricePoller::new(Watchlist::tokens(vec!["1".into()]), Duration::ZERO, 50)
+            .host(&host)
+            .max_polls(1);
+
+    let started = Instant::now();
+    poll(poller).await;
+    let failed = requests.lock().unwrap().len() as u32;
+    assert!(failed >= 3);
+    assert!(started.elapsed() >= Duration::from_millis(50) * failed);
+}
+
+#[tokio::test]
+async fn test_public_client_builds_without_a_key() {
+    use tickflow::connectors::polymarket::PolymarketClient;
+
+    let client = PolymarketClient::public(0);
+    assert!(!client.is_authenticated());
+    match client.authenticated_client().await {
+        Err(error) => assert!(error.to_string().contains("private key is required")),
+        Ok(_) => panic!("Expected a public client to refuse authenticated endpoints"),
+    }
+}
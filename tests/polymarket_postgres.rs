@@ -0,0 +1,79 @@
+//! Polymarket schema tests against a real database; skipped unless
+//! `TEST_DATABASE_URL` is set (e.g. `host=localhost user=postgres dbname=tickflow_test`).
+
+use std::sync::Arc;
+
+use serde_json::json;
+use tokio_postgres::{Client, NoTls};
+
+use tickflow::connectors::polymarket::types::PolymarketMessage;
+use tickflow::storage::postgres::DatabaseMessageHandler;
+use tickflow::storage::postgres_handler::polymarket::PolymarketMessageHandler;
+
+/// Connects to the test database with `search_path` set to a fresh `schema`.
+async fn connect(schema: &str) -> Option<Arc<Client>> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let (client, connection) = tokio_postgres::connect(&url, NoTls).await.unwrap();
+    tokio::spawn(connection);
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA IF EXISTS {schema} CASCADE;
+            CREATE SCHEMA {schema};
+            SET search_path TO {schema}"
+        ))
+        .await
+        .unwrap();
+    Some(Arc::new(client))
+}
+
+async fn count(client: &Client, table: &str) -> i64 {
+    client
+        .query_one(&format!("SELECT COUNT(*) FROM {table}"), &[])
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+async fn replayed_stream_messages_are_stored_once() {
+    let Some(client) = connect("polymarket_dedupe").await else {
+        return;
+    };
+    PolymarketMessageHandler
+        .initialize_schema(Arc::clone(&client))
+        .await
+        .unwrap();
+
+    let book = json!({
+        "asset_id": "123", "market": "0xabc", "timestamp": "1718000000000",
+        "bids": [{"price": "0.45", "size": "100"}], "asks": []
+    });
+    let change = json!({
+        "market": "0xabc", "timestamp": "1718000000001",
+        "price_changes": [
+            {"asset_id": "123", "price": "0.46", "size": "50", "side": "BUY"},
+            {"asset_id": "123", "price": "0.47", "size": "0", "side": "SELL", "hash": "0x1"}
+        ]
+    });
+    let trade = json!({
+        "asset_id": "123", "market": "0xabc", "price": "0.46", "size": "10",
+        "side": "BUY", "timestamp": "1718000000002"
+    });
+    let batch = vec![
+        PolymarketMessage::Book(serde_json::from_value(book).unwrap()),
+        PolymarketMessage::PriceChange(serde_json::from_value(change).unwrap()),
+        PolymarketMessage::LastTrade(serde_json::from_value(trade).unwrap()),
+    ];
+
+    // A reconnect replays the same messages
+    for _ in 0..2 {
+        PolymarketMessageHandler
+            .insert_batch(Arc::clone(&client), batch.clone())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(count(&client, "polymarket_book_snapshots").await, 1);
+    assert_eq!(count(&client, "polymarket_price_changes").await, 2);
+    assert_eq!(count(&client, "polymarket_last_trades").await, 1);
+}
@@ -0,0 +1,53 @@
+use tickflow::connectors::rate_limit::{HttpStatus, RateLimiter, is_rate_limited};
+use tokio::time::{Duration, Instant};
+
+/// Request error with the status of the response, if one arrived.
+struct RequestError(Option<u16>);
+
+impl HttpStatus for RequestError {
+    fn http_status(&self) -> Option<u16> {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn rate_limiter_spaces_requests_after_burst() {
+    let limiter = RateLimiter::new(50.0, 2);
+    let started = Instant::now();
+    for _ in 0..5 {
+        limiter.acquire().await;
+    }
+    // Two requests pass immediately, the remaining three wait 20ms each
+    assert!(started.elapsed() >= Duration::from_millis(55));
+}
+
+#[tokio::test]
+async fn rate_limiter_halves_on_429_and_recovers() {
+    let limiter = RateLimiter::new(10.0, 1);
+
+    let result: Result<(), _> = limiter.run(async { Err(RequestError(Some(429))) }).await;
+    assert!(result.is_err());
+    assert_eq!(limiter.rate(), 5.0);
+
+    let _: Result<(), _> = limiter.run(async { Err(RequestError(Some(404))) }).await;
+    assert_eq!(limiter.rate(), 5.0);
+
+    // Transport failures without a response never count as rate limiting
+    let _: Result<(), _> = limiter.run(async { Err(RequestError(None)) }).await;
+    assert_eq!(limiter.rate(), 5.0);
+
+    for _ in 0..10 {
+        limiter.on_success();
+    }
+    assert_eq!(limiter.rate(), 10.0);
+    assert!(is_rate_limited(&RequestError(Some(429))));
+}
+
+#[tokio::test]
+async fn rate_limiter_caps_the_wait_of_tiny_rates() {
+    let limiter = RateLimiter::new(0.0, 1);
+    limiter.acquire().await;
+    // Without a cap the wait overflows a Duration and panics
+    let waited = tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await;
+    assert!(waited.is_err());
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{TimeZone, Utc};
+use tokio::sync::mpsc;
+
+use tickflow::connectors::alpaca::types::AlpacaMessage;
+use tickflow::connectors::alpaca::{ReplaySource, ReplaySpeed};
+use tickflow::connectors::capture::CaptureRecorder;
+use tickflow::core::{MessageBatch, MessageSource};
+
+const AUTH_FRAME: &str = r#"[{"T":"success","msg":"authenticated"}]"#;
+const QUOTE_FRAME: &str = r#"[{"T":"q","S":"ETH/USD","bp":2500.1,"bs":1.5,"ap":2500.3,"as":2.0,"t":"2024-01-01T10:00:01Z"}]"#;
+
+fn capture_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tickflow-{name}-{}.jsonl", std::process::id()))
+}
+
+async fn write_capture(path: &PathBuf, source: &str) {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let mut recorder = CaptureRecorder::create(path, source).await.unwrap();
+    recorder.record_at(start, AUTH_FRAME).await.unwrap();
+    recorder.record_at(start, "not json").await.unwrap();
+    recorder
+        .record_at(start + chrono::Duration::seconds(2), QUOTE_FRAME)
+        .await
+        .unwrap();
+}
+
+async fn replay(source: &mut ReplaySource) -> anyhow::Result<Vec<MessageBatch<AlpacaMessage>>> {
+    let (tx, mut rx) = mpsc::channel(8);
+    source.run(tx).await?;
+    let mut batches = Vec::new();
+    while let Some(batch) = rx.recv().await {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+#[tokio::test]
+async fn replay_source_emits_recorded_frames_in_order() {
+    let path = capture_path("replay-fast");
+    write_capture(&path, "alpaca").await;
+
+    let batches = replay(&mut ReplaySource::new(&path)).await.unwrap();
+
+    assert_eq!(batches.len(), 2);
+    assert!(matches!(batches[0][0], AlpacaMessage::Success { .. }));
+    match &batches[1][0] {
+        AlpacaMessage::Quote(quote) => assert_eq!(quote.symbol, "ETH/USD"),
+        _ => panic!("Expected Quote message"),
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn replay_source_honours_speed_multiplier() {
+    let path = capture_path("replay-paced");
+    write_capture(&path, "alpaca").await;
+
+    let started = Instant::now();
+    let mut source = ReplaySource::new(&path).speed(ReplaySpeed::Paced { multiplier: 20.0 });
+    let batches = replay(&mut source).await.unwrap();
+
+    assert_eq!(batches.len(), 2);
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn replay_source_rejects_foreign_captures() {
+    let path = capture_path("replay-foreign");
+    write_capture(&path, "polymarket").await;
+
+    assert!(replay(&mut ReplaySource::new(&path)).await.is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn replay_source_rejects_invalid_speed_multipliers() {
+    let path = capture_path("replay-invalid-speed");
+    write_capture(&path, "alpaca").await;
+
+    for multiplier in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+        let mut source = ReplaySource::new(&path).speed(ReplaySpeed::Paced { multiplier });
+        assert!(replay(&mut source).await.is_err(), "{multiplier} accepted");
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use chrono::{NaiveDate, TimeZone, Utc};
+use tokio::sync::mpsc;
+
+use tickflow::config::{AppConfig, ScheduleConfig};
+use tickflow::core::{Message, MessageBatch, MessageSource};
+use tickflow::pipeline::calendar::UsEquityCalendar;
+use tickflow::pipeline::{PipelineStatus, Schedule, ScheduledSource};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tick(usize);
+
+impl Message for Tick {}
+
+/// Batch source that sends a single message and finishes.
+struct OneShot(usize);
+
+impl MessageSource<Tick> for OneShot {
+    fn run<'a>(
+        &'a mut self,
+        tx: mpsc::Sender<MessageBatch<Tick>>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            tx.send(vec![Tick(self.0)])
+                .await
+                .map_err(|err| anyhow!("send failed: {err}"))
+        })
+    }
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn calendar_knows_holidays_and_early_closes() {
+    assert!(!UsEquityCalendar::is_trading_day(date(2025, 4, 18))); // Good Friday
+    assert!(!UsEquityCalendar::is_trading_day(date(2025, 7, 4)));
+    assert!(!UsEquityCalendar::is_trading_day(date(2026, 7, 3))); // July 4th on a Saturday
+    assert!(!UsEquityCalendar::is_trading_day(date(2025, 6, 19)));
+    assert!(UsEquityCalendar::is_trading_day(date(2021, 12, 31))); // Saturday New Year not observed
+
+    // 1pm EDT early close, 4pm EST regular close
+    assert_eq!(
+        UsEquityCalendar::close(date(2025, 7, 3)),
+        Some(Utc.with_ymd_and_hms(2025, 7, 3, 17, 0, 0).unwrap())
+    );
+    assert_eq!(
+        UsEquityCalendar::close(date(2025, 12, 1)),
+        Some(Utc.with_ymd_and_hms(2025, 12, 1, 21, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn market_close_schedule_skips_weekends_and_holidays() {
+    let schedule = Schedule::AfterMarketClose(Duration::from_secs(30 * 60));
+    // After the early close run on Thursday July 3rd, the next is Monday July 7th
+    let after = Utc.with_ymd_and_hms(2025, 7, 3, 18, 0, 0).unwrap();
+    assert_eq!(
+        schedule.next_after(after),
+        Some(Utc.with_ymd_and_hms(2025, 7, 7, 20, 30, 0).unwrap())
+    );
+}
+
+#[test]
+fn cron_schedule_uses_standard_weekdays_and_time_zone() {
+    let schedule = Schedule::from_config(&ScheduleConfig {
+        cron: Some("30 18 * * 1-5".to_string()),
+        timezone: Some("America/New_York".to_string()),
+        ..Default::default()
+    })
+    .expect("valid schedule");
+
+    // Saturday -> Monday 18:30 EDT
+    let after = Utc.with_ymd_and_hms(2025, 7, 5, 12, 0, 0).unwrap();
+    assert_eq!(
+        schedule.next_after(after),
+        Some(Utc.with_ymd_and_hms(2025, 7, 7, 22, 30, 0).unwrap())
+    );
+}
+
+#[test]
+fn schedules_are_validated_with_the_pipeline() {
+    let config = AppConfig::from_toml(
+        r#"
+        [pipelines.statements]
+        source = "yahoo"
+        sink = "postgres"
+        schedule = { interval_secs = 60, cron = "0 * * * *" }
+
+        [pipelines.stream]
+        source = "alpaca"
+        sink = "postgres"
+        schedule = { cron = "not a cron" }
+        "#,
+    )
+    .expect("valid toml");
+
+    let error = config.pipeline("statements").unwrap_err().to_string();
+    assert!(error.contains("exactly one of"), "{error}");
+
+    let error = config.pipeline("stream").unwrap_err().to_string();
+    assert!(
+        error.contains("streaming sources cannot be scheduled"),
+        "{error}"
+    );
+    assert!(error.contains("invalid cron expression"), "{error}");
+}
+
+#[tokio::test]
+async fn scheduled_source_reruns_a_fresh_source_each_tick() {
+    let mut runs = 0;
+    let mut source =
+        ScheduledSource::new(Schedule::Interval(Duration::from_millis(5)), move || {
+            runs += 1;
+            Ok(OneShot(runs))
+        })
+        .max_runs(3);
+
+    let (tx, mut rx) = mpsc::channel(8);
+    source.run(tx).await.expect("scheduled source failed");
+
+    let mut received = Vec::new();
+    while let Some(batch) = rx.recv().await {
+        received.extend(batch);
+    }
+    assert_eq!(received, vec![Tick(1), Tick(2), Tick(3)]);
+}
+
+#[tokio::test]
+async fn failed_scheduled_runs_make_the_pipeline_unready() {
+    let status = PipelineStatus::new("statements");
+    status.set_running(true);
+    let mut runs = 0;
+    let mut source =
+        ScheduledSource::new(Schedule::Interval(Duration::from_millis(5)), move || {
+            runs += 1;
+            match runs {
+                1 => Err(anyhow!("proxy list is empty")),
+                _ => Ok(OneShot(runs)),
+            }
+        })
+        .max_runs(1)
+        .status(status.clone());
+
+    let (tx, _rx) = mpsc::channel(8);
+    source
+        .run(tx.clone())
+        .await
+        .expect("scheduled source failed");
+    let problems = status.snapshot().problems();
+    assert_eq!(problems.len(), 1, "{problems:?}");
+    assert!(problems[0].contains("proxy list is empty"), "{problems:?}");
+
+    // The next successful run clears the failure
+    source.run(tx).await.expect("scheduled source failed");
+    let snapshot = status.snapshot();
+    assert!(snapshot.problems().is_empty(), "{:?}", snapshot.problems());
+    assert_eq!(snapshot.errors, 1);
+}
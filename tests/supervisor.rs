@@ -0,0 +1,73 @@
+use std::future::{Ready, ready};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::{Result, anyhow};
+use tickflow::pipeline::{Restart, RestartPolicy, Supervisor};
+
+fn policy(policy: Restart, max_restarts: Option<u32>) -> RestartPolicy {
+    RestartPolicy {
+        policy,
+        max_restarts,
+        delay_secs: 0,
+    }
+}
+
+/// A pipeline that fails until it has been started `succeed_on` times.
+fn flaky(runs: Arc<AtomicU32>, succeed_on: u32) -> impl Fn() -> Ready<Result<()>> {
+    move || {
+        let run = runs.fetch_add(1, Ordering::SeqCst) + 1;
+        ready(if run >= succeed_on {
+            Ok(())
+        } else {
+            Err(anyhow!("run {run} failed"))
+        })
+    }
+}
+
+#[tokio::test]
+async fn restarts_failed_pipelines_until_they_succeed() {
+    let runs = Arc::new(AtomicU32::new(0));
+
+    Supervisor::new()
+        .pipeline(
+            "flaky",
+            policy(Restart::OnFailure, None),
+            flaky(runs.clone(), 3),
+        )
+        .run()
+        .await
+        .expect("pipeline eventually succeeds");
+
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn reports_pipelines_that_exhaust_their_restarts() {
+    let flaky_runs = Arc::new(AtomicU32::new(0));
+    let once_runs = Arc::new(AtomicU32::new(0));
+
+    let error = Supervisor::new()
+        .pipeline(
+            "flaky",
+            policy(Restart::OnFailure, Some(1)),
+            flaky(flaky_runs.clone(), 5),
+        )
+        .pipeline(
+            "healthy",
+            policy(Restart::Never, None),
+            flaky(once_runs.clone(), 1),
+        )
+        .run()
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert_eq!(flaky_runs.load(Ordering::SeqCst), 2);
+    assert_eq!(once_runs.load(Ordering::SeqCst), 1);
+    assert!(error.contains("1 pipeline(s) failed"), "{error}");
+    assert!(
+        error.contains("pipeline \"flaky\": run 2 failed"),
+        "{error}"
+    );
+}
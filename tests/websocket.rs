@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use futures_util::SinkExt;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::protocol::Message as WsMessage;
+
+use tickflow::connectors::websocket::{Control, Protocol, ReconnectPolicy, WebSocketSource};
+use tickflow::core::{Message, MessageSource};
+
+#[derive(Debug, Clone)]
+struct Frame(String);
+
+impl Message for Frame {}
+
+struct EchoProtocol {
+    url: String,
+}
+
+impl Protocol for EchoProtocol {
+    type Message = Frame;
+
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn auth_payload(&self) -> Option<String> {
+        None
+    }
+
+    fn subscribe_payload(&self) -> Option<String> {
+        None
+    }
+
+    fn parse(&self, frame: &str) -> anyhow::Result<Vec<Frame>> {
+        Ok(vec![Frame(frame.to_string())])
+    }
+
+    fn classify(&self, message: &Frame) -> Control {
+        match message.0.as_str() {
+            "auth failed" => Control::Error(message.0.clone()),
+            _ => Control::Data,
+        }
+    }
+}
+
+/// Serves websocket sessions that each send `frames` and close, counting connections.
+async fn serve(frames: Vec<&'static str>) -> (String, Arc<AtomicU32>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&connections);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let Ok(mut ws) = accept_async(stream).await else {
+                continue;
+            };
+            for frame in &frames {
+                let _ = ws.send(WsMessage::Text(frame.to_string())).await;
+            }
+            let _ = ws.close(None).await;
+        }
+    });
+    (url, connections)
+}
+
+fn policy(max_retries: u32) -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_retries: Some(max_retries),
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+    }
+}
+
+#[tokio::test]
+async fn sessions_dropped_without_data_count_toward_max_retries() {
+    let (url, connections) = serve(vec!["auth failed"]).await;
+    let mut source = WebSocketSource::with_protocol(EchoProtocol { url }).reconnect(policy(3));
+
+    let (tx, _rx) = mpsc::channel(64);
+    let result = tokio::time::timeout(Duration::from_secs(5), source.run(tx))
+        .await
+        .expect("source gave up");
+
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("without delivering data")
+    );
+    // The first session plus three reconnects
+    assert_eq!(connections.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn sessions_that_delivered_data_reset_the_retry_count() {
+    let (url, connections) = serve(vec!["tick"]).await;
+    let mut source = WebSocketSource::with_protocol(EchoProtocol { url }).reconnect(policy(1));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let run = tokio::spawn(async move { source.run(tx).await });
+    for _ in 0..5 {
+        let batch = rx.recv().await.expect("session delivered a frame");
+        assert_eq!(batch[0].0, "tick");
+    }
+    assert!(connections.load(Ordering::SeqCst) >= 5);
+
+    drop(rx);
+    run.abort();
+}
@@ -0,0 +1,457 @@
+//! Yahoo schema and TTM tests against a real database; skipped unless
+//! `TEST_DATABASE_URL` is set (e.g. `host=localhost user=postgres dbname=tickflow_test`).
+
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use tokio_postgres::{Client, NoTls};
+
+use tickflow::storage::InsertOutcome;
+use tickflow::storage::postgres::DatabaseMessageHandler;
+use tickflow::storage::postgres_handler::yahoo::{TtmTable, YahooMessageHandler};
+
+/// Connects to the test database with `search_path` set to a fresh `schema`.
+async fn connect(schema: &str) -> Option<Arc<Client>> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let (client, connection) = tokio_postgres::connect(&url, NoTls).await.unwrap();
+    tokio::spawn(connection);
+    client
+        .batch_execute(&format!(
+            "DROP SCHEMA IF EXISTS {schema} CASCADE;
+            CREATE SCHEMA {schema};
+            SET search_path TO {schema}"
+        ))
+        .await
+        .unwrap();
+    Some(Arc::new(client))
+}
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+async fn insert_quarter(client: &Client, period_date: &str, revenue: f64, income: Option<f64>) {
+    client
+        .execute(
+            "INSERT INTO income_statements (symbol, period_date, total_revenue, net_income)
+            VALUES ('AAPL', $1, $2, $3)",
+            &[&date(period_date), &revenue, &income],
+        )
+        .await
+        .unwrap();
+}
+
+async fn ttm_income(client: &Client, period_date: &str) -> Option<(f64, Option<f64>)> {
+    client
+        .query_opt(
+            "SELECT total_revenue, net_income FROM income_statements
+            WHERE symbol = 'AAPL' AND frequency = 'ttm' AND period_date = $1",
+            &[&date(period_date)],
+        )
+        .await
+        .unwrap()
+        .map(|row| (row.get(0), row.get(1)))
+}
+
+#[tokio::test]
+async fn quarterly_tables_are_migrated_with_the_frequency_check() {
+    let Some(client) = connect("yahoo_migration").await else {
+        return;
+    };
+    client
+        .batch_execute(
+            "CREATE TABLE quarterly_income_statements (
+                id SERIAL PRIMARY KEY,
+                symbol VARCHAR(10) NOT NULL,
+                period_date DATE,
+                total_revenue DOUBLE PRECISION,
+                gross_profit DOUBLE PRECISION,
+                operating_income DOUBLE PRECISION,
+                net_income DOUBLE PRECISION,
+                received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(symbol, period_date)
+            );
+            INSERT INTO quarterly_income_statements (symbol, period_date, total_revenue)
+            VALUES ('AAPL', '2024-03-31', 90.0)",
+        )
+        .await
+        .unwrap();
+
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+    // Running it again leaves the migrated table alone
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    let row = client
+        .query_one(
+            "SELECT frequency, total_revenue FROM income_statements WHERE symbol = 'AAPL'",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, String>(0), "quarterly");
+    assert_eq!(row.get::<_, f64>(1), 90.0);
+
+    // The same period may be stored once per frequency, but only known frequencies
+    client
+        .execute(
+            "INSERT INTO income_statements (symbol, frequency, period_date)
+            VALUES ('AAPL', 'annual', '2024-03-31')",
+            &[],
+        )
+        .await
+        .unwrap();
+    let bogus = client
+        .execute(
+            "INSERT INTO income_statements (symbol, frequency, period_date)
+            VALUES ('AAPL', 'monthly', '2024-03-31')",
+            &[],
+        )
+        .await;
+    assert!(bogus.is_err());
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_migration CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn migration_adds_the_check_to_tables_migrated_without_it() {
+    let Some(client) = connect("yahoo_recheck").await else {
+        return;
+    };
+    // A table left behind by a migration that did not add the check
+    client
+        .batch_execute(
+            "CREATE TABLE cashflow_statements (
+                id SERIAL PRIMARY KEY,
+                symbol VARCHAR(10) NOT NULL,
+                frequency VARCHAR(10) NOT NULL DEFAULT 'quarterly',
+                period_date DATE,
+                operating_cashflow DOUBLE PRECISION,
+                capital_expenditures DOUBLE PRECISION,
+                free_cash_flow DOUBLE PRECISION,
+                net_income DOUBLE PRECISION,
+                received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(symbol, frequency, period_date)
+            )",
+        )
+        .await
+        .unwrap();
+
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    let bogus = client
+        .execute(
+            "INSERT INTO cashflow_statements (symbol, frequency, period_date)
+            VALUES ('AAPL', 'monthly', '2024-03-31')",
+            &[],
+        )
+        .await;
+    assert!(bogus.is_err());
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_recheck CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ttm_rows_sum_four_consecutive_quarters() {
+    let Some(client) = connect("yahoo_ttm").await else {
+        return;
+    };
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    insert_quarter(&client, "2023-06-30", 10.0, Some(1.0)).await;
+    insert_quarter(&client, "2023-09-30", 20.0, Some(2.0)).await;
+    insert_quarter(&client, "2023-12-31", 30.0, None).await;
+
+    // Three quarters are not a full year
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2023-12-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Duplicate));
+    assert_eq!(ttm_income(&client, "2023-12-31").await, None);
+
+    insert_quarter(&client, "2024-03-31", 40.0, Some(4.0)).await;
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Inserted));
+    // A line item missing from any quarter has no TTM value
+    assert_eq!(ttm_income(&client, "2024-03-31").await, Some((100.0, None)));
+
+    // A restated quarter replaces the TTM row
+    client
+        .execute(
+            "UPDATE income_statements SET net_income = 3.0
+            WHERE frequency = 'quarterly' AND period_date = '2023-12-31'",
+            &[],
+        )
+        .await
+        .unwrap();
+    YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert_eq!(
+        ttm_income(&client, "2024-03-31").await,
+        Some((100.0, Some(10.0)))
+    );
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_ttm CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ttm_rows_need_quarters_within_a_year() {
+    let Some(client) = connect("yahoo_ttm_gap").await else {
+        return;
+    };
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    // A missing quarter stretches the last four over a whole year
+    insert_quarter(&client, "2023-03-31", 10.0, Some(1.0)).await;
+    insert_quarter(&client, "2023-09-30", 20.0, Some(2.0)).await;
+    insert_quarter(&client, "2023-12-31", 30.0, Some(3.0)).await;
+    insert_quarter(&client, "2024-03-31", 40.0, Some(4.0)).await;
+
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Duplicate));
+    assert_eq!(ttm_income(&client, "2024-03-31").await, None);
+
+    // Once the gap is filled the last four quarters are consecutive
+    insert_quarter(&client, "2023-06-30", 15.0, Some(1.5)).await;
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Inserted));
+    assert_eq!(
+        ttm_income(&client, "2024-03-31").await,
+        Some((105.0, Some(10.5)))
+    );
+
+    // Quarters after the TTM period are not counted
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2023-09-30"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Duplicate));
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_ttm_gap CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ttm_balance_sheets_copy_their_quarter() {
+    let Some(client) = connect("yahoo_ttm_balance").await else {
+        return;
+    };
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    client
+        .execute(
+            "INSERT INTO balance_sheets (symbol, period_date, total_assets, shares_outstanding)
+            VALUES ('AAPL', '2024-03-31', 500.0, 15000)",
+            &[],
+        )
+        .await
+        .unwrap();
+    let outcome = YahooMessageHandler::insert_ttm(
+        &client,
+        TtmTable::BalanceSheet,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Inserted));
+
+    let row = client
+        .query_one(
+            "SELECT total_assets, shares_outstanding FROM balance_sheets
+            WHERE frequency = 'ttm' AND period_date = '2024-03-31'",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, f64>(0), 500.0);
+    assert_eq!(row.get::<_, i64>(1), 15000);
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_ttm_balance CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn split_adjusted_bars_divide_by_every_later_split() {
+    use chrono::{DateTime, Utc};
+    use rust_decimal::Decimal;
+    use tickflow::connectors::yahoo::types::{BarInterval, PriceBar, Split, YahooMessage};
+
+    let Some(client) = connect("yahoo_splits").await else {
+        return;
+    };
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    let at = |s: &str| format!("{s}T00:00:00Z").parse::<DateTime<Utc>>().unwrap();
+    let bar = |day: &str, close: i64| {
+        let close = Decimal::from(close);
+        YahooMessage::PriceBar(
+            PriceBar::new("NVDA", BarInterval::Day, at(day), [close; 4], Some(100)).unwrap(),
+        )
+    };
+    let split = |day: &str, numerator, denominator| {
+        YahooMessage::Split(Split::new("NVDA", at(day), numerator, denominator).unwrap())
+    };
+    YahooMessageHandler
+        .insert_batch(
+            client.clone(),
+            vec![
+                bar("2021-07-01", 800),
+                bar("2024-06-01", 1200),
+                bar("2024-06-11", 120),
+                split("2021-07-20", 4, 1),
+                split("2024-06-10", 10, 1),
+            ],
+        )
+        .await
+        .unwrap();
+
+    let rows = client
+        .query(
+            "SELECT ts::date::text, close, volume FROM split_adjusted_price_bars
+            WHERE symbol = 'NVDA' ORDER BY ts",
+            &[],
+        )
+        .await
+        .unwrap();
+    let adjusted: Vec<(String, f64, i64)> = rows
+        .iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    // Prices divide and volumes multiply by the splits after each bar
+    let expected = [
+        ("2021-07-01", 20.0, 4000),
+        ("2024-06-01", 120.0, 1000),
+        ("2024-06-11", 120.0, 100),
+    ];
+    assert_eq!(adjusted.len(), expected.len());
+    for ((day, close, volume), (expected_day, expected_close, expected_volume)) in
+        adjusted.into_iter().zip(expected)
+    {
+        assert_eq!(day, expected_day);
+        assert!((close - expected_close).abs() < 1e-9, "{day}: {close}");
+        assert_eq!(volume, expected_volume);
+    }
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_splits CASCADE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ttm_line_items_do_not_mix_currencies_with_unknown_ones() {
+    let Some(client) = connect("yahoo_ttm_currency").await else {
+        return;
+    };
+    YahooMessageHandler
+        .initialize_schema(client.clone())
+        .await
+        .unwrap();
+
+    let quarters = ["2023-06-30", "2023-09-30", "2023-12-31", "2024-03-31"];
+    for (idx, quarter) in quarters.iter().enumerate() {
+        let revenue_currency = (idx != 2).then_some("USD");
+        client
+            .execute(
+                "INSERT INTO financial_line_items
+                    (symbol, frequency, period_date, statement, item, value, currency)
+                VALUES ('AAPL', 'quarterly', $1, 'income_statement', 'total_revenue', 10, $2),
+                    ('AAPL', 'quarterly', $1, 'income_statement', 'net_income', 1, NULL)",
+                &[&date(quarter), &revenue_currency],
+            )
+            .await
+            .unwrap();
+    }
+
+    let outcome = YahooMessageHandler::insert_ttm_line_items(
+        &client,
+        TtmTable::IncomeStatement,
+        "AAPL",
+        date("2024-03-31"),
+    )
+    .await;
+    assert!(matches!(outcome, InsertOutcome::Inserted));
+
+    let rows = client
+        .query(
+            "SELECT item, value::TEXT, currency FROM financial_line_items
+            WHERE frequency = 'ttm' ORDER BY item",
+            &[],
+        )
+        .await
+        .unwrap();
+    let ttm: Vec<(String, String, Option<String>)> = rows
+        .iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    // A quarter without a currency cannot be summed with USD ones
+    assert_eq!(ttm, [("net_income".to_string(), "4".to_string(), None)]);
+
+    client
+        .batch_execute("DROP SCHEMA yahoo_ttm_currency CASCADE")
+        .await
+        .unwrap();
+}